@@ -0,0 +1,38 @@
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use bounce::{expected_aggregate, precommit_message, CommitBuilder, LocalSigner};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// Numbers of signatures aggregated, i.e. of Bounce units in a constellation.
+const SIZES: [usize; 4] = [4, 16, 64, 256];
+
+fn aggregate(c: &mut Criterion) {
+    let msg = precommit_message(&[], 0, 1);
+
+    let mut group = c.benchmark_group("aggregate");
+    for &n in SIZES.iter() {
+        let commits: Vec<_> = (0..n)
+            .map(|id| {
+                CommitBuilder::precommit(1, 0, msg.clone())
+                    .signer_id(id as u32)
+                    .sign_with(&LocalSigner::random())
+                    .unwrap()
+                    .build()
+            })
+            .collect();
+
+        // Make sure what is measured is a valid aggregate.
+        let (signature, public_key) = expected_aggregate(&commits).unwrap();
+        assert!(Bn256.verify(&signature, &msg, &public_key).is_ok());
+
+        // Reported as signatures aggregated per second.
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &commits, |b, commits| {
+            b.iter(|| expected_aggregate(commits).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, aggregate);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+use crate::{AggregateError, Commit};
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use std::collections::HashSet;
+
+/// Aggregate built one commit at a time, keeping the running aggregate signature and public key
+/// so that it can be checked after every addition. A corrupted commit is then caught as soon as
+/// it's added, instead of only once the whole aggregate fails to verify.
+#[derive(Clone, Debug, Default)]
+pub struct IncrementalAggregator {
+    // Running aggregate signature and public key, compressed. Empty until a commit is added.
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+    // Public keys of the signers added so far.
+    signers: HashSet<Vec<u8>>,
+}
+
+impl IncrementalAggregator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Folds the signature and public key of `commit` into the running aggregate. A signer can't
+    /// be added twice.
+    pub fn add(&mut self, commit: &Commit) -> Result<(), AggregateError> {
+        if self.signers.contains(&commit.public_key) {
+            return Err(AggregateError::SignerCountMismatch {
+                public_keys: self.signers.len() + 1,
+                signers: self.signers.len(),
+            });
+        }
+
+        if self.signers.is_empty() {
+            // Checks that the points are valid.
+            self.signature = Bn256
+                .aggregate_signatures(&[&commit.signature])
+                .map_err(AggregateError::Bls)?;
+            self.public_key = Bn256
+                .aggregate_public_keys(&[&commit.public_key])
+                .map_err(AggregateError::Bls)?;
+        } else {
+            let signature = Bn256
+                .aggregate_signatures(&[&self.signature, &commit.signature])
+                .map_err(AggregateError::Bls)?;
+            self.public_key = Bn256
+                .aggregate_public_keys(&[&self.public_key, &commit.public_key])
+                .map_err(AggregateError::Bls)?;
+            self.signature = signature;
+        }
+        self.signers.insert(commit.public_key.clone());
+        Ok(())
+    }
+
+    /// Whether the running aggregate signature verifies against `msg` and the running aggregate
+    /// public key. False if no commit was added.
+    pub fn verify_current(&self, msg: &[u8]) -> bool {
+        !self.is_empty() && Bn256.verify(&self.signature, msg, &self.public_key).is_ok()
+    }
+
+    /// Running aggregate signature and aggregate public key, in that order, or None if no commit
+    /// was added.
+    pub fn current(&self) -> Option<(&[u8], &[u8])> {
+        if self.is_empty() {
+            return None;
+        }
+        Some((&self.signature, &self.public_key))
+    }
+
+    /// Number of signers added.
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+}
+
+/// Bitmap of signer ids, most significant bit first, so that signer 0 is the first bit of the
+/// first byte. Aggregates of the same slot are ordered canonically by the lexicographic order of
+/// their bitmaps, and the smallest one is adopted by every Bounce unit.
+pub fn signer_bitmap(signers: &[u32]) -> Vec<u8> {
+    let len = signers.iter().max().map_or(0, |&max| max as usize / 8 + 1);
+    let mut bitmap = vec![0; len];
+    for &id in signers {
+        bitmap[id as usize / 8] |= 0x80 >> (id % 8);
+    }
+    bitmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expected_aggregate, precommit_message, CommitBuilder, LocalSigner};
+
+    #[test]
+    fn signer_bitmap_order() {
+        assert_eq!(signer_bitmap(&[]), Vec::<u8>::new());
+        assert_eq!(signer_bitmap(&[0, 9, 2]), vec![0b1010_0000, 0b0100_0000]);
+
+        // The set without the smallest signer the sets differ in comes first.
+        assert!(signer_bitmap(&[1, 2, 3]) < signer_bitmap(&[0, 1, 2]));
+        assert!(signer_bitmap(&[0, 1, 2]) < signer_bitmap(&[0, 1, 2, 8]));
+    }
+
+    #[test]
+    fn verify_current() {
+        let msg = precommit_message(&[], 0, 1);
+        let commits: Vec<_> = (0..3)
+            .map(|id| {
+                CommitBuilder::precommit(1, 0, msg.clone())
+                    .signer_id(id)
+                    .sign_with(&LocalSigner::random())
+                    .unwrap()
+                    .build()
+            })
+            .collect();
+
+        let mut aggregator = IncrementalAggregator::new();
+        assert!(!aggregator.verify_current(&msg));
+        for commit in commits.iter() {
+            aggregator.add(commit).unwrap();
+            assert!(aggregator.verify_current(&msg));
+        }
+        assert!(matches!(
+            aggregator.add(&commits[0]),
+            Err(AggregateError::SignerCountMismatch { .. })
+        ));
+
+        // The same aggregate as when folding all commits at once.
+        let (signature, public_key) = expected_aggregate(&commits).unwrap();
+        assert_eq!(
+            aggregator.current(),
+            Some((signature.as_slice(), public_key.as_slice()))
+        );
+
+        // A commit signed over another message breaks the aggregate as soon as it's added.
+        let bad = CommitBuilder::precommit(1, 0, precommit_message(&[], 0, 2))
+            .signer_id(3)
+            .sign_with(&LocalSigner::random())
+            .unwrap()
+            .build();
+        aggregator.add(&bad).unwrap();
+        assert!(!aggregator.verify_current(&msg));
+        assert_eq!(aggregator.len(), 4);
+    }
+}
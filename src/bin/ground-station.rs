@@ -74,7 +74,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     .derive_public_key(&ground_station_private_key)
                     .unwrap();
                 let signature = Bn256
-                    .sign(&ground_station_private_key, &msg.as_bytes())
+                    .sign(&ground_station_private_key, msg.as_bytes())
                     .unwrap();
 
                 let precommit = Commit {
@@ -87,6 +87,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     aggregated: false,
                     // TODO: FIXME
                     signer_id: 100,
+                    ..Default::default()
                 };
 
                 let request = tonic::Request::new(precommit);
@@ -97,8 +98,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 let end = chrono::Utc::now();
 
-                let _ = Bn256
-                    .verify(&response.signature, &msg.as_bytes(), &response.public_key)
+                Bn256
+                    .verify(&response.signature, msg.as_bytes(), &response.public_key)
                     .unwrap();
 
                 info!(
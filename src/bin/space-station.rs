@@ -1,17 +1,18 @@
 use bounce::bounce_satellite_server::{BounceSatellite, BounceSatelliteServer};
-use bounce::{configure_log, configure_log_to_file, BounceConfig, Commit, Cubesat, Phase};
+use bounce::{
+    configure_log, configure_log_to_file, is_slot_message, phase_at, verify_signature,
+    BounceConfig, Commit, Cubesat, CubesatHandle, RunOutcome, SlotDriver, TimerDriver,
+};
 use clap::{crate_authors, crate_version, App, Arg};
 // use bounce::Cubesat;
-use log::info;
-use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, Mutex};
-use tokio::time::{interval, interval_at, Instant};
+use log::{info, warn};
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
 use tonic::{transport::Server, Request, Response, Status};
 
 pub struct CubesatInfo {
-    id: u32,
-    _handle: tokio::task::JoinHandle<()>,
-    request_tx: mpsc::Sender<Commit>,
+    handle: CubesatHandle,
+    _join_handle: tokio::task::JoinHandle<RunOutcome>,
 }
 
 pub struct SpaceStation {
@@ -19,59 +20,32 @@ pub struct SpaceStation {
     result_rx: Mutex<mpsc::Receiver<Commit>>,
     // The last slot index for which this Space station responded.
     last_slot: Mutex<u32>,
+    bounce_config: BounceConfig,
+    // When the driver started the first slot, right after the Bounce units subscribed to it.
+    slots_start: Instant,
 
     cubesat_infos: Vec<CubesatInfo>,
 }
 
-// Timer thread which brodacsts phase transitions.
-async fn timer(timer_tx: broadcast::Sender<Phase>, bounce_config: BounceConfig) {
-    let slot_duration = Duration::from_secs(bounce_config.slot_duration as u64);
-    let start = Instant::now();
-    let phase2_start = start + Duration::from_secs(bounce_config.phase1_duration as u64);
-    let phase3_start = phase2_start + Duration::from_secs(bounce_config.phase2_duration as u64);
-    let mut slot_ticker = interval(slot_duration);
-    let mut phase2_ticker = interval_at(phase2_start, slot_duration);
-    let mut phase3_ticker = interval_at(phase3_start, slot_duration);
-
-    timer_tx.send(Phase::First).unwrap();
-    loop {
-        tokio::select! {
-            _ = slot_ticker.tick() => {
-                timer_tx.send(Phase::First).unwrap();
-            }
-            _ = phase2_ticker.tick() => {
-                timer_tx.send(Phase::Second).unwrap();
-            }
-            _ = phase3_ticker.tick() => {
-                timer_tx.send(Phase::Third).unwrap();
-            }
-        }
-    }
-}
-
 impl SpaceStation {
-    pub fn new(num_cubesats: u32, timer_tx: &broadcast::Sender<Phase>) -> SpaceStation {
+    pub fn new(bounce_config: &BounceConfig, driver: &dyn SlotDriver) -> SpaceStation {
         let (result_tx, result_rx) = mpsc::channel(25);
 
         let result_rx = Mutex::new(result_rx);
 
         let mut cubesat_infos = Vec::new();
 
-        for id in 0..num_cubesats {
-            let timer_rx = timer_tx.subscribe();
-            let (request_tx, request_rx) = mpsc::channel(25);
-
-            let result_tx = result_tx.clone();
-            let handle = tokio::spawn(async move {
-                let mut cubesat =
-                    Cubesat::new(id as usize, num_cubesats, result_tx, request_rx, timer_rx);
-                cubesat.run().await;
-            });
+        for id in 0..bounce_config.num_cubesats {
+            let (handle, join_handle) = Cubesat::spawn(
+                id as usize,
+                bounce_config.clone(),
+                result_tx.clone(),
+                driver.subscribe(),
+            );
 
             cubesat_infos.push(CubesatInfo {
-                id,
-                _handle: handle,
-                request_tx,
+                handle,
+                _join_handle: join_handle,
             });
         }
 
@@ -80,9 +54,16 @@ impl SpaceStation {
         Self {
             result_rx,
             last_slot,
+            bounce_config: bounce_config.clone(),
+            slots_start: Instant::now(),
             cubesat_infos,
         }
     }
+
+    // Index of the slot the Bounce units are in.
+    fn current_slot(&self) -> u32 {
+        phase_at(&self.bounce_config, self.slots_start, Instant::now()).0 + 1
+    }
 }
 
 #[tonic::async_trait]
@@ -102,13 +83,28 @@ impl BounceSatellite for SpaceStation {
     async fn bounce(&self, request: Request<Commit>) -> Result<Response<Commit>, Status> {
         info!("Space Station\tReceived a request: {:?}", request);
 
-        let commit: Commit = request.into_inner();
+        let mut commit: Commit = request.into_inner();
+
+        // Bounce units would drop it, leaving this request waiting for an aggregate forever.
+        if !verify_signature(&commit) {
+            warn!(
+                "Space Station\tRejected a commit with an invalid signature from {}",
+                commit.signer_id
+            );
+            return Err(Status::invalid_argument("invalid signature"));
+        }
+
+        // A ground station doesn't follow the slots, and only signs its message, so its commit is
+        // assigned to the current slot. Bounce units drop commits of other slots.
+        if !commit.aggregated && !is_slot_message(&self.bounce_config.domain, &commit.msg) {
+            commit.i = self.current_slot();
+        }
 
         for cubesat_info in &self.cubesat_infos {
-            if cubesat_info.request_tx.send(commit.clone()).await.is_err() {
+            if cubesat_info.handle.submit(commit.clone()).await.is_err() {
                 info!(
                     "Space Station\tFailed to send a request to Bounce Unit {}",
-                    cubesat_info.id
+                    cubesat_info.handle.id()
                 );
             }
         }
@@ -138,10 +134,10 @@ impl BounceSatellite for SpaceStation {
                         );
                         // TODO: Do not send to the cubesat that has sent this precommit.
                         for cubesat_info in &self.cubesat_infos {
-                            if cubesat_info.request_tx.send(commit.clone()).await.is_err() {
+                            if cubesat_info.handle.submit(commit.clone()).await.is_err() {
                                 info!(
                                     "Space Station\tFailed to send a request to Bounce Unit {}",
-                                    cubesat_info.id
+                                    cubesat_info.handle.id()
                                 );
                             }
                         }
@@ -188,6 +184,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Specify a directory to save logs.")
                 .default_value("log"),
         )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .short("c")
+                .value_name("CONFIG")
+                .help("Specify a TOML file to load the Bounce configuration from."),
+        )
         .get_matches();
 
     let addr = matches.value_of("addr").unwrap();
@@ -203,21 +206,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let socket_addr = format!("{}:{}", addr, port).parse()?;
 
-    let bounce_config = BounceConfig {
-        num_cubesats: 5,
-        slot_duration: 10,
-        phase1_duration: 4,
-        phase2_duration: 4,
+    let bounce_config = match matches.value_of("config") {
+        Some(path) => BounceConfig::from_toml(path)?,
+        None => BounceConfig {
+            num_cubesats: 5,
+            slot_duration: 10,
+            phase1_duration: 4,
+            phase2_duration: 4,
+            ..Default::default()
+        },
     };
 
-    // Initialized to Stop
-    let (timer_tx, _timer_rx) = broadcast::channel(15);
-
-    let comms_hub = SpaceStation::new(bounce_config.num_cubesats, &timer_tx);
-
-    tokio::spawn(async move {
-        timer(timer_tx, bounce_config).await;
-    });
+    let mut driver = TimerDriver::new(bounce_config.clone());
+    let comms_hub = SpaceStation::new(&bounce_config, &driver);
+    driver.start();
 
     // This installs a BounceSatelliteServer service.
     // Question: could this actually successfully make RPCs over unreliable connections between
@@ -0,0 +1,111 @@
+use crate::{command_message, Phase, Signer, SignerError, SlotRecord};
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use tokio::sync::oneshot;
+
+/// Control messages sent to a running Bounce unit.
+#[derive(Debug)]
+pub enum Command {
+    /// Stop the Bounce unit, making `Cubesat::run` return `RunOutcome::Terminated`. Termination is
+    /// final and idempotent: further `Terminate`s, and later calls to `run`, don't shut down again.
+    Terminate,
+    /// Stop all activity: phase transitions are ignored and received commits are dropped until
+    /// `Resume`.
+    Pause,
+    /// Resume the activity stopped by `Pause`.
+    Resume,
+    /// Stop advancing to the next slot while otherwise staying online: the current slot goes
+    /// through its phases, commits are still processed and queries answered, but the start of the
+    /// next slot is ignored until `UnfreezeSlot`.
+    FreezeSlot,
+    /// Advance to the next slot again after `FreezeSlot`.
+    UnfreezeSlot,
+    /// Abandon the aggregation of the current slot: its collected commits are discarded and the
+    /// Bounce unit abstains, neither signing nor aggregating, until the next slot.
+    AbortSlot,
+    /// Ask for the outcome of slot i. The reply is None if the slot isn't in the slot history,
+    /// e.g. because it's older than the retention window.
+    SlotStatus {
+        i: u32,
+        reply: oneshot::Sender<Option<SlotRecord>>,
+    },
+    /// Ask for the current phase, as driven by the timer.
+    GetPhase(oneshot::Sender<Phase>),
+    /// A command for Bounce unit `target` along with the signature of its `message` by the
+    /// controller. Bounce units configured with a `BounceConfig::controller_key` only act on
+    /// commands signed by it for them, each counter at most once.
+    Signed {
+        command: Box<Command>,
+        target: u32,
+        counter: u64,
+        signature: Vec<u8>,
+    },
+}
+
+impl Command {
+    /// Message a controller signs to authenticate this command to Bounce unit `target` with the
+    /// given counter, or None if the command is already signed.
+    pub fn message(&self, domain: &[u8], target: u32, counter: u64) -> Option<Vec<u8>> {
+        let name = match self {
+            Command::Terminate => "terminate".to_string(),
+            Command::Pause => "pause".to_string(),
+            Command::Resume => "resume".to_string(),
+            Command::FreezeSlot => "freeze_slot".to_string(),
+            Command::UnfreezeSlot => "unfreeze_slot".to_string(),
+            Command::AbortSlot => "abort_slot".to_string(),
+            Command::SlotStatus { i, .. } => format!("slot_status({})", i),
+            Command::GetPhase(_) => "get_phase".to_string(),
+            Command::Signed { .. } => return None,
+        };
+        Some(command_message(domain, &name, target, counter))
+    }
+
+    /// This command signed by a controller, for Bounce unit `target` of the constellation
+    /// `domain` configured with the controller's public key. The counter must be greater than
+    /// the one of any command the controller signed for that Bounce unit before. A command which
+    /// is already signed is returned as is.
+    pub fn signed(
+        self,
+        domain: &[u8],
+        target: u32,
+        counter: u64,
+        signer: &dyn Signer,
+    ) -> Result<Command, SignerError> {
+        let msg = match self.message(domain, target, counter) {
+            Some(msg) => msg,
+            None => return Ok(self),
+        };
+        Ok(Command::Signed {
+            signature: signer.sign(&msg)?,
+            command: Box::new(self),
+            target,
+            counter,
+        })
+    }
+
+    /// The command signed by the controller with the given compressed public key for Bounce unit
+    /// `target`, unwrapped from its signature along with its counter, or None if it isn't signed
+    /// by that controller for that Bounce unit.
+    pub fn authenticated(
+        self,
+        domain: &[u8],
+        controller_key: &[u8],
+        target: u32,
+    ) -> Option<(Command, u64)> {
+        match self {
+            Command::Signed {
+                command,
+                target: signed_target,
+                counter,
+                signature,
+            } if signed_target == target => {
+                let msg = command.message(domain, target, counter)?;
+                Bn256
+                    .verify(&signature, &msg, controller_key)
+                    .ok()
+                    .map(|_| (*command, counter))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::commit::CommitType;
+use crate::{Commit, Signer, SignerError, SIGNATURE_SCHEME_VERSION};
+
+/// Builds a `Commit`. The fields every commit needs are arguments of `CommitBuilder::new`, the
+/// others default to those of an unsigned, non-aggregated commit.
+#[derive(Clone, Debug)]
+pub struct CommitBuilder {
+    commit: Commit,
+}
+
+impl CommitBuilder {
+    pub fn new(typ: CommitType, i: u32, j: u32, msg: Vec<u8>) -> Self {
+        CommitBuilder {
+            commit: Commit {
+                typ: typ.into(),
+                i,
+                j,
+                msg,
+                aggregated: false,
+                version: SIGNATURE_SCHEME_VERSION,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn precommit(i: u32, j: u32, msg: Vec<u8>) -> Self {
+        CommitBuilder::new(CommitType::Precommit, i, j, msg)
+    }
+
+    pub fn noncommit(i: u32, j: u32, msg: Vec<u8>) -> Self {
+        CommitBuilder::new(CommitType::Noncommit, i, j, msg)
+    }
+
+    pub fn signer_id(mut self, signer_id: u32) -> Self {
+        self.commit.signer_id = signer_id;
+        self
+    }
+
+    pub fn payload(mut self, payload: Vec<u8>) -> Self {
+        self.commit.payload = payload;
+        self
+    }
+
+    /// Marks the commit as an aggregate of the given signers.
+    pub fn aggregated(mut self, signers: Vec<u32>) -> Self {
+        self.commit.aggregated = true;
+        self.commit.signer_count = signers.len() as u32;
+        self.commit.signers = signers;
+        self
+    }
+
+    pub fn public_key(mut self, public_key: Vec<u8>) -> Self {
+        self.commit.public_key = public_key;
+        self
+    }
+
+    pub fn signature(mut self, signature: Vec<u8>) -> Self {
+        self.commit.signature = signature;
+        self
+    }
+
+    /// Signs the message, filling in the signature and public key.
+    pub fn sign_with(mut self, signer: &dyn Signer) -> Result<Self, SignerError> {
+        self.commit.signature = signer.sign(&self.commit.msg)?;
+        self.commit.public_key = signer.public_key().into_bytes();
+        Ok(self)
+    }
+
+    pub fn build(self) -> Commit {
+        self.commit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalSigner;
+    use bls_signatures_rs::bn256::Bn256;
+    use bls_signatures_rs::MultiSignature;
+
+    #[test]
+    fn commit_builder_sign() {
+        let signer = LocalSigner::random();
+        let msg = crate::precommit_message(&[], 0, 1);
+
+        let unsigned = CommitBuilder::precommit(1, 1, msg.clone())
+            .signer_id(2)
+            .build();
+        assert!(!unsigned.aggregated);
+        assert!(unsigned.signature.is_empty());
+
+        let commit = CommitBuilder::precommit(1, 1, msg.clone())
+            .signer_id(2)
+            .sign_with(&signer)
+            .unwrap()
+            .build();
+
+        assert_eq!(commit.typ(), CommitType::Precommit);
+        assert_eq!((commit.i, commit.j, commit.signer_id), (1, 1, 2));
+        assert!(!commit.aggregated);
+        assert_eq!(commit.public_key, signer.public_key().as_bytes());
+        assert!(Bn256
+            .verify(&commit.signature, &msg, &commit.public_key)
+            .is_ok());
+    }
+}
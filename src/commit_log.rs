@@ -0,0 +1,59 @@
+use crate::{Commit, Cubesat, ProcessOutcome};
+use prost::Message;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Append-only log of commits, stored as length-delimited protobuf entries.
+#[derive(Debug)]
+pub struct CommitLog {
+    file: File,
+}
+
+impl CommitLog {
+    /// Opens the log at `path` for appending, creating it if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(CommitLog { file })
+    }
+
+    /// Appends a commit. The entry is written with a single write, so that an interrupted
+    /// append can at most truncate the last entry.
+    pub fn append(&mut self, commit: &Commit) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(commit.encoded_len() + 10);
+        commit
+            .encode_length_delimited(&mut buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.file.write_all(&buf)
+    }
+
+    /// Reads back all commits of the log at `path`, in the order they were appended.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<Commit>> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        let mut buf = bytes.as_slice();
+        let mut commits = Vec::new();
+        while !buf.is_empty() {
+            let commit = Commit::decode_length_delimited(&mut buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            commits.push(commit);
+        }
+        Ok(commits)
+    }
+}
+
+/// Feeds the commits of the log at `path` through `cubesat` in the order they were logged, e.g.
+/// to reproduce a past session when debugging a disputed finalization. The log records neither
+/// when commits arrived nor phase transitions, so the Bounce unit must first be brought to the
+/// slot and phase the session started in. Returns the outcome of each commit.
+pub async fn replay_log<P: AsRef<Path>>(
+    path: P,
+    cubesat: &mut Cubesat,
+) -> io::Result<Vec<ProcessOutcome>> {
+    let mut outcomes = Vec::new();
+    for commit in CommitLog::read(path)? {
+        outcomes.push(cubesat.process(commit).await);
+    }
+    Ok(outcomes)
+}
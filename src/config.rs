@@ -0,0 +1,308 @@
+use crate::bounce_config::{KeyChangePolicy, Phase3Policy};
+use crate::{supermajority, BounceConfig, Phase, PhaseSet, PublicKey};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Errors returned when loading a `BounceConfig` from a file.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// A required field is missing.
+    MissingField(&'static str),
+    /// A field has a value outside of those it accepts.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read config: {}", e),
+            ConfigError::Parse(e) => write!(f, "failed to parse config: {}", e),
+            ConfigError::MissingField(field) => write!(f, "missing config field `{}`", field),
+            ConfigError::InvalidValue { field, value } => {
+                write!(f, "invalid value `{}` for config field `{}`", value, field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+// Fields as written in a config file. Durations are in seconds, as in `BounceConfig`. Unknown
+// fields, e.g. misspelled ones, are an error rather than silently ignored.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    num_cubesats: Option<u32>,
+    slot_duration: Option<u32>,
+    phase1_duration: Option<u32>,
+    phase2_duration: Option<u32>,
+    domain: Option<String>,
+    observer: Option<bool>,
+    sign_max_attempts: Option<u32>,
+    clock_skew_tolerance_ms: Option<u32>,
+    phase3_policy: Option<String>,
+    allow_empty_message: Option<bool>,
+    epoch_slots: Option<u32>,
+    verify_threads: Option<u32>,
+    key_change_policy: Option<String>,
+    min_aggregate_interval_ms: Option<u32>,
+    strict: Option<bool>,
+    checkpoint_every: Option<u32>,
+    leader: Option<u32>,
+    uncompressed_points: Option<bool>,
+    slot_cpu_budget_ms: Option<u32>,
+    startup_grace_ms: Option<u32>,
+    // Hex encoded.
+    controller_key: Option<String>,
+    verify_yield_every: Option<u32>,
+    downshift_on_unreachable_quorum: Option<bool>,
+    heartbeat_interval_ms: Option<u32>,
+    // Numbers of the enabled phases, e.g. [1] for phase 1 only.
+    enabled_phases: Option<Vec<u32>>,
+    aggregate_max_retries: Option<u32>,
+    threshold: Option<u32>,
+    canonical_messages_only: Option<bool>,
+}
+
+impl BounceConfig {
+    /// Phases of a slot which are enabled, every phase unless set otherwise.
+    pub fn enabled_phases(&self) -> PhaseSet {
+        match self.enabled_phases {
+            0 => PhaseSet::ALL,
+            bits => PhaseSet::from_bits(bits),
+        }
+    }
+
+    pub fn set_enabled_phases(&mut self, phases: PhaseSet) {
+        self.enabled_phases = phases.bits();
+    }
+
+    /// Number of signatures needed to aggregate among `n` Bounce units: `threshold` if set, but
+    /// never less than supermajority.
+    pub fn required_signers(&self, n: usize) -> usize {
+        supermajority(n).max(self.threshold as usize)
+    }
+
+    /// Loads a config from a TOML file. `num_cubesats` and the three durations are required, the
+    /// other fields default to those of `BounceConfig::default()`.
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> Result<BounceConfig, ConfigError> {
+        BounceConfig::from_toml_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<BounceConfig, ConfigError> {
+        let file: FileConfig = toml::from_str(s)?;
+
+        let mut config = BounceConfig {
+            num_cubesats: file
+                .num_cubesats
+                .ok_or(ConfigError::MissingField("num_cubesats"))?,
+            slot_duration: file
+                .slot_duration
+                .ok_or(ConfigError::MissingField("slot_duration"))?,
+            phase1_duration: file
+                .phase1_duration
+                .ok_or(ConfigError::MissingField("phase1_duration"))?,
+            phase2_duration: file
+                .phase2_duration
+                .ok_or(ConfigError::MissingField("phase2_duration"))?,
+            domain: file.domain.unwrap_or_default().into_bytes(),
+            observer: file.observer.unwrap_or_default(),
+            sign_max_attempts: file.sign_max_attempts.unwrap_or_default(),
+            clock_skew_tolerance_ms: file.clock_skew_tolerance_ms.unwrap_or_default(),
+            allow_empty_message: file.allow_empty_message.unwrap_or_default(),
+            epoch_slots: file.epoch_slots.unwrap_or_default(),
+            verify_threads: file.verify_threads.unwrap_or_default(),
+            min_aggregate_interval_ms: file.min_aggregate_interval_ms.unwrap_or_default(),
+            strict: file.strict.unwrap_or_default(),
+            checkpoint_every: file.checkpoint_every.unwrap_or_default(),
+            leader: file.leader,
+            uncompressed_points: file.uncompressed_points.unwrap_or_default(),
+            slot_cpu_budget_ms: file.slot_cpu_budget_ms.unwrap_or_default(),
+            startup_grace_ms: file.startup_grace_ms.unwrap_or_default(),
+            verify_yield_every: file.verify_yield_every.unwrap_or_default(),
+            downshift_on_unreachable_quorum: file
+                .downshift_on_unreachable_quorum
+                .unwrap_or_default(),
+            heartbeat_interval_ms: file.heartbeat_interval_ms.unwrap_or_default(),
+            aggregate_max_retries: file.aggregate_max_retries.unwrap_or_default(),
+            threshold: file.threshold.unwrap_or_default(),
+            canonical_messages_only: file.canonical_messages_only.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let n = config.num_cubesats as usize;
+        if config.threshold != 0 && !(supermajority(n)..=n).contains(&(config.threshold as usize)) {
+            return Err(ConfigError::InvalidValue {
+                field: "threshold",
+                value: config.threshold.to_string(),
+            });
+        }
+
+        if let Some(key) = file.controller_key {
+            config.controller_key = hex::decode(&key)
+                .ok()
+                .filter(|bytes| PublicKey::from_bytes(bytes).is_ok())
+                .ok_or(ConfigError::InvalidValue {
+                    field: "controller_key",
+                    value: key,
+                })?;
+        }
+
+        if let Some(numbers) = file.enabled_phases {
+            let mut phases = PhaseSet::FIRST_ONLY;
+            for number in numbers {
+                let phase = match number {
+                    1 => Phase::First,
+                    2 => Phase::Second,
+                    3 => Phase::Third,
+                    _ => {
+                        return Err(ConfigError::InvalidValue {
+                            field: "enabled_phases",
+                            value: number.to_string(),
+                        })
+                    }
+                };
+                phases = phases.with(&phase);
+            }
+            config.set_enabled_phases(phases);
+        }
+
+        if let Some(policy) = file.phase3_policy {
+            let policy = match policy.as_str() {
+                "abstain_if_precommitted" => Phase3Policy::AbstainIfPrecommitted,
+                "always_noncommit" => Phase3Policy::AlwaysNoncommit,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        field: "phase3_policy",
+                        value: policy,
+                    })
+                }
+            };
+            config.set_phase3_policy(policy);
+        }
+
+        if let Some(policy) = file.key_change_policy {
+            let policy = match policy.as_str() {
+                "accept" => KeyChangePolicy::Accept,
+                "reject" => KeyChangePolicy::Reject,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        field: "key_change_policy",
+                        value: policy,
+                    })
+                }
+            };
+            config.set_key_change_policy(policy);
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn config_from_toml() {
+        let path = std::env::temp_dir().join(format!(
+            "bounce-config-{}-{}.toml",
+            std::process::id(),
+            thread_rng().gen::<u64>()
+        ));
+        fs::write(
+            &path,
+            r#"
+num_cubesats = 10
+slot_duration = 10
+phase1_duration = 4
+phase2_duration = 3
+domain = "constellation-a"
+phase3_policy = "always_noncommit"
+"#,
+        )
+        .unwrap();
+
+        let config = BounceConfig::from_toml(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.num_cubesats, 10);
+        assert_eq!(config.slot_duration, 10);
+        assert_eq!(config.phase1_duration, 4);
+        assert_eq!(config.phase2_duration, 3);
+        assert_eq!(config.domain, b"constellation-a".to_vec());
+        assert_eq!(config.phase3_policy(), Phase3Policy::AlwaysNoncommit);
+        assert!(!config.observer);
+
+        let missing = BounceConfig::from_toml_str("num_cubesats = 10\nslot_duration = 10\n");
+        assert!(matches!(
+            missing,
+            Err(ConfigError::MissingField("phase1_duration"))
+        ));
+
+        let invalid_key = BounceConfig::from_toml_str(
+            "num_cubesats = 10\nslot_duration = 10\nphase1_duration = 4\nphase2_duration = 3\n\
+             controller_key = \"0102\"\n",
+        );
+        assert!(matches!(
+            invalid_key,
+            Err(ConfigError::InvalidValue {
+                field: "controller_key",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_config_field() {
+        let misspelled = BounceConfig::from_toml_str(
+            "num_cubesats = 10\nslot_duraton = 10\nphase1_duration = 4\nphase2_duration = 3\n",
+        );
+        assert!(matches!(misspelled, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn threshold_from_toml() {
+        let base =
+            "num_cubesats = 10\nslot_duration = 10\nphase1_duration = 4\nphase2_duration = 3\n";
+
+        let config = BounceConfig::from_toml_str(base).unwrap();
+        assert_eq!(config.required_signers(10), 7);
+
+        let config = BounceConfig::from_toml_str(&format!("{}threshold = 9\n", base)).unwrap();
+        assert_eq!(config.required_signers(10), 9);
+
+        for threshold in &[6, 11] {
+            let invalid =
+                BounceConfig::from_toml_str(&format!("{}threshold = {}\n", base, threshold));
+            assert!(matches!(
+                invalid,
+                Err(ConfigError::InvalidValue {
+                    field: "threshold",
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -1,10 +1,224 @@
+use crate::bounce_config::{KeyChangePolicy, Phase3Policy};
 use crate::commit::CommitType;
-use crate::{supermajority, Commit, Phase, SlotInfo};
-use bls_signatures_rs::bn256::Bn256;
+use crate::{
+    derive_epoch_key, derive_unit_key, heartbeat_message, in_domain, is_canonical_message,
+    is_justified_by, is_slot_message, noncommit_message, partition_valid, precommit_message,
+    signer_bitmap, verify_aggregate, verify_aggregate_against, verify_signature,
+    verify_signatures_with, with_uncompressed_points, BounceConfig, Command, Commit, CommitLog,
+    CubesatHandle, CubesatMetrics, Event, LocalSigner, MetricsHandle, PeerRegistry, Phase,
+    PublicKey, SignatureVerifier, Signer, SignerError, SlotHistory, SlotInfo, VerifyError,
+    SIGNATURE_SCHEME_VERSION,
+};
+use bls_signatures_rs::bn256::{error::Error as BlsError, Bn256};
 use bls_signatures_rs::MultiSignature;
-use log::info;
-use rand::{thread_rng, Rng};
+use log::{error, info, warn};
+use prost::Message;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+// Capacities of the channels created by `Cubesat::spawn`.
+const REQUEST_CHANNEL_CAPACITY: usize = 25;
+const COMMAND_CHANNEL_CAPACITY: usize = 4;
+
+// Finalized aggregates buffered for each stream returned by `Cubesat::finalized_stream`. Slower
+// consumers skip the oldest ones.
+const FINALIZED_CHANNEL_CAPACITY: usize = 16;
+
+// How long a deferred result waits for room in the channel to the communications hub before it's
+// dropped.
+const RESULT_RETRY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Minimum interval between broadcasts of the aggregate of a slot when
+// `BounceConfig::min_aggregate_interval_ms` isn't set.
+const AGGREGATE_REBROADCAST_INTERVAL: Duration = Duration::from_secs(1);
+
+// Signing attempts when `BounceConfig::sign_max_attempts` isn't set, and the delay before the
+// first retry. The delay doubles after every failed attempt.
+const DEFAULT_SIGN_MAX_ATTEMPTS: u32 = 3;
+const SIGN_RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
+// Commits of a batch verified on the task of a Bounce unit between two yields when
+// `BounceConfig::verify_yield_every` isn't set.
+const DEFAULT_VERIFY_YIELD_EVERY: usize = 16;
+
+// Commits set aside once the slot processing budget is exhausted. Further commits are dropped.
+const DEFERRED_COMMITS_CAPACITY: usize = 256;
+
+/// Errors returned when folding commits into an aggregate signature.
+#[derive(Debug)]
+pub enum AggregateError {
+    /// The number of public keys folded doesn't match the number of distinct signers, which means
+    /// the same commit was collected more than once.
+    SignerCountMismatch { public_keys: usize, signers: usize },
+    /// The commits don't all sign the same message, so their aggregate couldn't verify.
+    MessageMismatch { messages: usize },
+    /// The underlying BLS aggregation failed, e.g. due to a malformed key or signature.
+    Bls(BlsError),
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::SignerCountMismatch {
+                public_keys,
+                signers,
+            } => write!(
+                f,
+                "{} public keys folded for {} distinct signers",
+                public_keys, signers
+            ),
+            AggregateError::MessageMismatch { messages } => {
+                write!(f, "commits sign {} distinct messages", messages)
+            }
+            AggregateError::Bls(e) => write!(f, "BLS aggregation failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AggregateError {}
+
+/// Aggregate signature and aggregate public key, in that order, which an honest Bounce unit
+/// produces from the given commits. Lets clients and tests compute or check an aggregate without
+/// running a `Cubesat`. Both are compressed points, as emitted unless
+/// `BounceConfig::uncompressed_points` is set.
+pub fn expected_aggregate(commits: &[Commit]) -> Result<(Vec<u8>, Vec<u8>), AggregateError> {
+    Cubesat::aggregate(commits)
+}
+
+// Splits commits into those signing the message most of them sign, and the others. Ties go to
+// the message of the earliest commit.
+fn split_by_majority_message(commits: &[Commit]) -> (Vec<Commit>, Vec<Commit>) {
+    let majority = commits
+        .iter()
+        .enumerate()
+        .max_by_key(|(k, c)| {
+            let count = commits.iter().filter(|other| other.msg == c.msg).count();
+            (count, std::cmp::Reverse(*k))
+        })
+        .map(|(_, c)| c.msg.clone());
+
+    commits
+        .iter()
+        .cloned()
+        .partition(|c| Some(&c.msg) == majority.as_ref())
+}
+
+/// Application-level check of the message of a received commit, returning whether it's accepted.
+pub type MsgValidator = Box<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Hook called with the aggregate finalizing a slot, e.g. to anchor it to an external ledger.
+pub type FinalizeHook = Box<dyn Fn(&Commit) + Send + Sync>;
+
+/// Why a received commit wasn't counted towards an aggregate.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DropReason {
+    /// The commit was signed by this Bounce unit.
+    OwnCommit,
+    /// The Bounce unit isn't taking part in a slot.
+    Stopped,
+    /// The slot was already aggregated.
+    AlreadyAggregated,
+    /// A commit from the same signer was already collected.
+    Duplicate,
+    /// The commit was signed for another constellation.
+    WrongDomain,
+    /// The signature doesn't verify.
+    InvalidSignature,
+    /// The commit type isn't handled in the current phase, e.g. a noncommit in phase 1.
+    WrongPhase,
+    /// The commit signs a different message, e.g. another payload, than the commits of the same
+    /// type already collected for this slot.
+    MessageMismatch,
+    /// The raw input couldn't be decoded into a commit.
+    Decode,
+    /// The commit has an empty message, which `BounceConfig::allow_empty_message` doesn't allow.
+    EmptyMessage,
+    /// The aggregate represents fewer signers than required, or claims more signers than the
+    /// signer ids it carries.
+    InsufficientSigners,
+    /// The message validator rejected the message.
+    InvalidMessage,
+    /// The aggregation of the current slot was abandoned by `Command::AbortSlot`.
+    SlotAborted,
+    /// The commit is a partial aggregate, which only coordinators consume.
+    PartialAggregate,
+    /// The signer presented another public key than the one learned for its id, and
+    /// `BounceConfig::key_change_policy` rejects key changes.
+    KeyChanged,
+    /// The justification of the precommit isn't a valid aggregate committing slot j, or its
+    /// digest isn't folded into the signed message.
+    InvalidJustification,
+    /// The type of the commit is none of `CommitType`.
+    UnknownType,
+    /// The commit is a heartbeat, which only shows that its signer is alive.
+    Heartbeat,
+    /// The commit was signed with a version of the signature scheme newer than
+    /// `SIGNATURE_SCHEME_VERSION`.
+    UnsupportedVersion,
+    /// The commit's message isn't the canonical one of its type, slot indices, payload and
+    /// justification, e.g. to pass a signature off for another slot. Free-form messages are only
+    /// dropped with `BounceConfig::canonical_messages_only`.
+    NonCanonicalMessage,
+    /// The commit is for another slot than the current one. With `BounceConfig::strict` the
+    /// Bounce unit also stops.
+    SlotMismatch,
+}
+
+/// Result of processing a single commit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessOutcome {
+    /// Whether this Bounce unit signed a precommit or noncommit while processing the commit.
+    pub signed: bool,
+    /// Whether the slot got aggregated, either by this Bounce unit or by accepting the aggregate
+    /// of a peer.
+    pub aggregated: bool,
+    /// Why the commit was dropped, if it was.
+    pub dropped: Option<DropReason>,
+}
+
+/// Reason why `Cubesat::run` returned, so that a supervisor can decide whether to restart it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunOutcome {
+    /// A `Command::Terminate` was received.
+    Terminated,
+    /// The channel delivering commits from the communications hub was closed.
+    InputClosed,
+    /// The channel delivering commands was closed, so the Bounce unit could no longer be
+    /// terminated or paused.
+    CommandChannelClosed,
+    /// The Bounce unit can no longer take part in the protocol, e.g. because the channel to the
+    /// communications hub was closed.
+    FatalErrors,
+    /// The configuration can't be run, e.g. `num_cubesats` is zero.
+    ConfigInvalid,
+    /// The cancellation token passed to `Cubesat::run_until_cancelled` was cancelled.
+    Cancelled,
+    /// A protocol violation was observed in strict mode.
+    ProtocolViolation(ProtocolViolation),
+}
+
+/// Protocol violations which stop a Bounce unit when `BounceConfig::strict` is set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProtocolViolation {
+    /// The signature of a commit doesn't verify.
+    InvalidSignature { signer_id: u32 },
+    /// A signer signed two different messages of the same commit type for the slot.
+    Equivocation { signer_id: u32 },
+    /// An aggregate conflicting with the one accepted for the slot was received.
+    ConflictingAggregate { i: u32 },
+    /// A commit was signed for another slot than the current one.
+    SlotMismatch { signer_id: u32, i: u32 },
+}
 
 /// Bounce Unit invariants
 /// 1. A Bounce unit will never send a precommit or non-commit if it has already sent a precommit
@@ -17,11 +231,20 @@ pub struct Cubesat {
     id: usize,
 
     // Configuration for slot
-    num_cubesats: u32,
+    config: BounceConfig,
     slot_info: SlotInfo,
+    // Known Bounce units of the constellation. When present, the number of registered units
+    // takes precedence over `config.num_cubesats`.
+    registry: Option<PeerRegistry>,
 
-    public_key: Vec<u8>,
-    private_key: Vec<u8>,
+    public_key: PublicKey,
+    signer: Box<dyn Signer>,
+
+    metrics: MetricsHandle,
+    // Optional observer of notable events.
+    events_tx: Option<mpsc::UnboundedSender<Event>>,
+    // Sender of the aggregates finalizing each slot, to the streams of `finalized_stream`.
+    finalized_tx: broadcast::Sender<Commit>,
 
     // sender to send to communications hub
     result_tx: mpsc::Sender<Commit>,
@@ -30,6 +253,58 @@ pub struct Cubesat {
 
     // Receiver for phase transitions.
     timer_rx: broadcast::Receiver<Phase>,
+    // Receiver for commands controlling this Bounce unit.
+    command_rx: mpsc::Receiver<Command>,
+
+    // Set when a commit couldn't be sent to the communications hub.
+    result_closed: bool,
+    // Whether activity is suspended by `Command::Pause`.
+    paused: bool,
+    // Whether advancing to the next slot is suspended by `Command::FreezeSlot`.
+    slot_frozen: bool,
+    // Number of precommits and noncommits this Bounce unit signed, unlike the metrics never shared
+    // with other Bounce units.
+    signed_count: u64,
+    // When this Bounce unit was created or last reset, from which the startup grace period runs.
+    started_at: Instant,
+    // When phase 1 of the current slot was entered.
+    slot_started: Option<Instant>,
+    // Slot and time the aggregate of a slot was last emitted, accepted or re-broadcast.
+    last_aggregate: Option<(u32, Instant)>,
+    // Optional record of every commit received.
+    commit_log: Option<CommitLog>,
+    // When the phase 3 noncommit of this Bounce unit is due, if it's been delayed to tolerate
+    // clock skew.
+    noncommit_at: Option<Instant>,
+    // When the own aggregate of the current slot is broadcast again unless it's acknowledged in
+    // the meantime, and how many retries are left.
+    aggregate_retry: Option<(Instant, u32)>,
+    // Optional application-level check of the message of received commits.
+    msg_validator: Option<MsgValidator>,
+    // Optional hook called with the aggregate of every slot which finalizes.
+    on_finalize: Option<FinalizeHook>,
+    // Check of the signature of received commits.
+    signature_verifier: SignatureVerifier,
+    // Channel through which commits received by `run` come back once their signature is
+    // verified, along with whether it's valid.
+    verified_tx: mpsc::UnboundedSender<(Commit, bool)>,
+    verified_rx: mpsc::UnboundedReceiver<(Commit, bool)>,
+    // Commits received after the slot processing budget was exhausted, oldest first.
+    deferred: VecDeque<Commit>,
+    // Seed from which the signing key of each epoch is derived, if keys are rotated.
+    epoch_master: Option<Vec<u8>>,
+    // Epoch of the current signing key.
+    epoch: Option<u64>,
+    // Public keys of peers learned from verified commits, the last one presented by each id.
+    learned_keys: PeerRegistry,
+    // Protocol violation which stopped this Bounce unit in strict mode, until `run` reports it.
+    violation: Option<ProtocolViolation>,
+    // Set once a `Command::Terminate` was received, after which this Bounce unit stays
+    // terminated.
+    terminating: bool,
+    // Counter of the last command signed by the controller this Bounce unit acted on. Signed
+    // commands whose counter isn't greater are replays.
+    last_command_counter: Option<u64>,
 }
 
 impl Cubesat {
@@ -39,34 +314,168 @@ impl Cubesat {
         result_tx: mpsc::Sender<Commit>,
         request_rx: mpsc::Receiver<Commit>,
         timer_rx: broadcast::Receiver<Phase>,
+        command_rx: mpsc::Receiver<Command>,
     ) -> Self {
-        let mut rng = thread_rng();
+        let config = BounceConfig {
+            num_cubesats,
+            ..Default::default()
+        };
+        Cubesat::with_config(id, config, result_tx, request_rx, timer_rx, command_rx)
+    }
+
+    /// Creates a Bounce unit whose first slot is `start_slot`, e.g. to join a constellation which
+    /// is already running without dropping commits as stale while catching up.
+    pub fn new_at_slot(
+        id: usize,
+        num_cubesats: u32,
+        result_tx: mpsc::Sender<Commit>,
+        request_rx: mpsc::Receiver<Commit>,
+        timer_rx: broadcast::Receiver<Phase>,
+        command_rx: mpsc::Receiver<Command>,
+        start_slot: u32,
+    ) -> Self {
+        let mut cubesat = Cubesat::new(
+            id,
+            num_cubesats,
+            result_tx,
+            request_rx,
+            timer_rx,
+            command_rx,
+        );
+        cubesat.slot_info = SlotInfo::starting_at(start_slot);
+        cubesat
+    }
 
+    pub fn with_config(
+        id: usize,
+        config: BounceConfig,
+        result_tx: mpsc::Sender<Commit>,
+        request_rx: mpsc::Receiver<Commit>,
+        timer_rx: broadcast::Receiver<Phase>,
+        command_rx: mpsc::Receiver<Command>,
+    ) -> Self {
         // generate public and private key pairs.
-        let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let public_key = Bn256.derive_public_key(&private_key).unwrap();
+        let signer = LocalSigner::random();
+        let public_key = signer.public_key();
         let slot_info = SlotInfo::new();
+        let (verified_tx, verified_rx) = mpsc::unbounded_channel();
 
         Cubesat {
             id,
-            num_cubesats,
+            config,
             slot_info,
+            registry: None,
             public_key,
-            private_key,
+            signer: Box::new(signer),
+            metrics: MetricsHandle::new(),
+            events_tx: None,
+            finalized_tx: broadcast::channel(FINALIZED_CHANNEL_CAPACITY).0,
             result_tx,
             request_rx,
             timer_rx,
+            command_rx,
+            result_closed: false,
+            paused: false,
+            slot_frozen: false,
+            signed_count: 0,
+            started_at: Instant::now(),
+            slot_started: None,
+            last_aggregate: None,
+            commit_log: None,
+            noncommit_at: None,
+            aggregate_retry: None,
+            msg_validator: None,
+            on_finalize: None,
+            signature_verifier: Arc::new(verify_signature),
+            verified_tx,
+            verified_rx,
+            deferred: VecDeque::new(),
+            epoch_master: None,
+            epoch: None,
+            learned_keys: PeerRegistry::new(),
+            violation: None,
+            terminating: false,
+            last_command_counter: None,
+        }
+    }
+
+    /// Creates a Bounce unit whose key pair is derived from `seed` and its id, so that the same
+    /// seed always yields the same constellation. Meant for reproducible tests, as anyone knowing
+    /// the seed knows every private key.
+    pub fn deterministic(
+        id: usize,
+        seed: &[u8],
+        config: BounceConfig,
+        result_tx: mpsc::Sender<Commit>,
+        request_rx: mpsc::Receiver<Commit>,
+        timer_rx: broadcast::Receiver<Phase>,
+        command_rx: mpsc::Receiver<Command>,
+    ) -> Self {
+        let signer = LocalSigner::new(derive_unit_key(seed, id as u64));
+        let mut cubesat =
+            Cubesat::with_config(id, config, result_tx, request_rx, timer_rx, command_rx);
+        cubesat.set_signer(Box::new(signer));
+        cubesat
+    }
+
+    /// Creates a Bounce unit and runs it on a new task, returning a handle to interact with it
+    /// along with the handle of the task.
+    pub fn spawn(
+        id: usize,
+        config: BounceConfig,
+        result_tx: mpsc::Sender<Commit>,
+        timer_rx: broadcast::Receiver<Phase>,
+    ) -> (CubesatHandle, JoinHandle<RunOutcome>) {
+        let (request_tx, request_rx) = mpsc::channel(REQUEST_CHANNEL_CAPACITY);
+        let (command_tx, command_rx) = mpsc::channel(COMMAND_CHANNEL_CAPACITY);
+
+        let mut handle = CubesatHandle::new(id, request_tx, command_tx);
+        if !config.controller_key.is_empty() {
+            handle = handle.requiring_signed_commands();
         }
+        let mut cubesat =
+            Cubesat::with_config(id, config, result_tx, request_rx, timer_rx, command_rx);
+        let join_handle = tokio::spawn(async move { cubesat.run().await });
+
+        (handle, join_handle)
     }
 
-    fn aggregate(commits: &[Commit]) -> (Vec<u8>, Vec<u8>) {
+    fn aggregate(commits: &[Commit]) -> Result<(Vec<u8>, Vec<u8>), AggregateError> {
+        // Each signer must be represented exactly once, otherwise the aggregate would claim more
+        // signatures than there are signers.
+        let signers: HashSet<&[u8]> = commits.iter().map(|c| c.public_key.as_slice()).collect();
+        if signers.len() != commits.len() {
+            return Err(AggregateError::SignerCountMismatch {
+                public_keys: commits.len(),
+                signers: signers.len(),
+            });
+        }
+
+        // Signatures over different messages can be folded together, but the aggregate would
+        // verify against none of them.
+        let messages: HashSet<&[u8]> = commits.iter().map(|c| c.msg.as_slice()).collect();
+        if messages.len() > 1 {
+            return Err(AggregateError::MessageMismatch {
+                messages: messages.len(),
+            });
+        }
+
+        // Aggregate in the order of signer ids, so that the same signers always yield the same
+        // bytes whatever order their commits were collected in.
+        let mut commits: Vec<&Commit> = commits.iter().collect();
+        commits.sort_by(|a, b| (a.signer_id, &a.public_key).cmp(&(b.signer_id, &b.public_key)));
+
         let sig_refs: Vec<&[u8]> = commits.iter().map(|c| c.signature.as_slice()).collect();
-        let aggregate_signature = Bn256.aggregate_signatures(&sig_refs).unwrap();
+        let aggregate_signature = Bn256
+            .aggregate_signatures(&sig_refs)
+            .map_err(AggregateError::Bls)?;
 
         let public_key_refs: Vec<&[u8]> = commits.iter().map(|c| c.public_key.as_slice()).collect();
-        let aggregate_public_key = Bn256.aggregate_public_keys(&public_key_refs).unwrap();
+        let aggregate_public_key = Bn256
+            .aggregate_public_keys(&public_key_refs)
+            .map_err(AggregateError::Bls)?;
 
-        (aggregate_signature, aggregate_public_key)
+        Ok((aggregate_signature, aggregate_public_key))
     }
 
     fn get_commits(&self, commit_type: CommitType) -> &[Commit] {
@@ -77,578 +486,5817 @@ impl Cubesat {
         }
     }
 
-    async fn aggregate_and_broadcast(&mut self, mut commit: Commit) {
-        let (aggregate_signature, aggregate_public_key) =
-            Cubesat::aggregate(self.get_commits(commit.typ()));
+    fn get_commits_mut(&mut self, commit_type: CommitType) -> &mut Vec<Commit> {
+        if commit_type == CommitType::Precommit {
+            &mut self.slot_info.precommits
+        } else {
+            &mut self.slot_info.noncommits
+        }
+    }
 
-        commit.signature = aggregate_signature;
-        commit.public_key = aggregate_public_key;
-        commit.aggregated = true;
-        commit.i = self.slot_info.i;
-        commit.signer_id = self.id as u32;
+    /// Sets a channel on which notable events, such as safety violations, are reported.
+    pub fn set_event_sender(&mut self, events_tx: mpsc::UnboundedSender<Event>) {
+        self.events_tx = Some(events_tx);
+    }
+
+    /// Stream of the aggregates finalizing each slot from now on, whether produced by this
+    /// Bounce unit or received from a peer.
+    pub fn finalized_stream(&self) -> impl Stream<Item = Commit> {
+        // A consumer lagging behind skips the aggregates it missed.
+        BroadcastStream::new(self.finalized_tx.subscribe()).filter_map(|aggregate| aggregate.ok())
+    }
+
+    // Marks the current slot as finalized with the given aggregate.
+    fn finalize(&mut self, aggregate: Commit) {
+        let (from, to) = (self.slot_info.j, aggregate.j);
+        if to > from.saturating_add(1) {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommitGap: last committed slot advanced from {} to {}",
+                self.slot_info.i, self.id, from, to,
+            );
+            self.emit(Event::CommitGap { from, to });
+        }
+
+        self.last_aggregate = Some((self.slot_info.i, Instant::now()));
+        self.slot_info.finalize(aggregate.clone());
+        if let Some(on_finalize) = &self.on_finalize {
+            on_finalize(&aggregate);
+        }
+        // Nobody may be listening.
+        let _ = self.finalized_tx.send(aggregate);
+    }
+
+    fn emit(&self, event: Event) {
+        if let Some(events_tx) = &self.events_tx {
+            // The observer going away doesn't affect the protocol.
+            let _ = events_tx.send(event);
+        }
+    }
+
+    pub fn set_registry(&mut self, registry: PeerRegistry) {
+        if registry.len() != self.config.num_cubesats as usize {
+            warn!(
+                "Bounce Unit {}\tnum_cubesats is {} but {} peers are registered, using the latter",
+                self.id,
+                self.config.num_cubesats,
+                registry.len(),
+            );
+        }
+        self.registry = Some(registry);
+        if !self.quorum_reachable() {
+            warn!(
+                "Bounce Unit {}\tQuorumUnreachable: {} Bounce units reachable, {} needed",
+                self.id,
+                self.num_reachable(),
+                self.threshold(),
+            );
+        }
+    }
+
+    /// Records every commit received, before it's processed, to the given log.
+    pub fn set_commit_log(&mut self, commit_log: CommitLog) {
+        self.commit_log = Some(commit_log);
+    }
+
+    /// Sets a check of the message of received commits, e.g. that an embedded block hash is
+    /// known, in addition to signature verification. Commits whose message is rejected are
+    /// dropped.
+    pub fn set_msg_validator(&mut self, msg_validator: MsgValidator) {
+        self.msg_validator = Some(msg_validator);
+    }
+
+    /// Sets a hook called once for every slot which finalizes, with its aggregate, whether it was
+    /// produced by this Bounce unit or received from a peer.
+    pub fn set_on_finalize(&mut self, on_finalize: FinalizeHook) {
+        self.on_finalize = Some(on_finalize);
+    }
+
+    /// Writes a CSV header to `writer`, then appends a row per finalized slot with
+    /// `i,j,commit_type,signer_count,elapsed_ms`, where `elapsed_ms` is the time since the sink
+    /// was installed. The sink is installed as the hook of `set_on_finalize`, replacing any hook
+    /// set before.
+    pub fn csv_sink<W: Write + Send + 'static>(&mut self, mut writer: W) -> io::Result<()> {
+        writer.write_all(b"i,j,commit_type,signer_count,elapsed_ms\n")?;
+        writer.flush()?;
+
+        let id = self.id;
+        let started = Instant::now();
+        let writer = std::sync::Mutex::new(writer);
+        self.set_on_finalize(Box::new(move |aggregate: &Commit| {
+            let commit_type = match aggregate.typ() {
+                CommitType::Precommit => "precommit",
+                CommitType::Noncommit => "noncommit",
+                CommitType::Heartbeat => "heartbeat",
+            };
+            let row = format!(
+                "{},{},{},{},{}\n",
+                aggregate.i,
+                aggregate.j,
+                commit_type,
+                aggregate.signer_count,
+                started.elapsed().as_millis(),
+            );
+
+            let mut writer = match writer.lock() {
+                Ok(writer) => writer,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            if let Err(e) = writer
+                .write_all(row.as_bytes())
+                .and_then(|_| writer.flush())
+            {
+                // Analysis output isn't worth stopping the protocol for.
+                warn!(
+                    "Slot {}\tBounce Unit {}\tCsvSink: failed to write row: {}",
+                    aggregate.i, id, e,
+                );
+            }
+        }));
+        Ok(())
+    }
+
+    /// Replaces the check of the signature of received commits, e.g. with one backed by a
+    /// hardware accelerator.
+    pub fn set_signature_verifier(&mut self, signature_verifier: SignatureVerifier) {
+        self.signature_verifier = signature_verifier;
+    }
 
-        self.slot_info.aggregated = true;
-        if commit.typ() == CommitType::Precommit {
-            self.slot_info.j = commit.i;
+    /// Signs with keys derived from `master` from now on, rotating to the key of each new epoch
+    /// of `config.epoch_slots` slots. Each new public key is published as an `Event::EpochKey`.
+    pub fn set_epoch_master(&mut self, master: Vec<u8>) {
+        self.epoch_master = Some(master);
+        self.epoch = None;
+        self.rotate_epoch_key();
+    }
+
+    /// Epoch of the current signing key, if keys are derived from a master seed.
+    pub fn epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    // Switches to the key of the epoch of the current slot, if it isn't the current one.
+    fn rotate_epoch_key(&mut self) {
+        let master = match &self.epoch_master {
+            Some(master) => master,
+            None => return,
+        };
+
+        let epoch = match self.config.epoch_slots {
+            0 => 0,
+            epoch_slots => (self.slot_info.i / epoch_slots) as u64,
+        };
+        if self.epoch == Some(epoch) {
+            return;
         }
+
+        let signer = LocalSigner::new(derive_epoch_key(master, epoch));
+        let public_key = signer.public_key();
+        self.set_signer(Box::new(signer));
+        self.epoch = Some(epoch);
+
+        let id = self.id as u32;
+        if let Some(registry) = self.registry.as_mut() {
+            if registry.contains(id) {
+                registry.insert(id, public_key.as_bytes().to_vec());
+            }
+        }
+
         info!(
-            "Slot {}\tBounce Unit {}\tCommit Type {:?}\taggregated and broadcast",
+            "Slot {}\tBounce Unit {}\tEpochKey: rotated to epoch {}, public key {}",
             self.slot_info.i,
             self.id,
-            commit.typ(),
+            epoch,
+            hex::encode(&public_key),
         );
-        self.result_tx.send(commit).await.unwrap();
+        self.emit(Event::EpochKey { epoch, public_key });
     }
 
-    async fn sign_and_broadcast(&mut self, mut commit: Commit) -> Commit {
-        let signature = Bn256.sign(&self.private_key, &commit.msg).unwrap();
-        commit.signature = signature;
-        commit.public_key = self.public_key.to_vec();
-        commit.i = self.slot_info.i;
-        commit.signer_id = self.id as u32;
+    /// Public keys of peers learned from the commits they signed.
+    pub fn learned_keys(&self) -> &PeerRegistry {
+        &self.learned_keys
+    }
 
-        self.slot_info.signed = true;
-        self.result_tx.send(commit.clone()).await.unwrap();
+    // Learns the public key of the signer of a verified commit. Returns false if the signer
+    // presented another key than the one learned and the policy rejects key changes.
+    fn learn_key(&mut self, commit: &Commit) -> bool {
+        let previous = match self.learned_keys.get(commit.signer_id) {
+            Some(previous) if previous == commit.public_key.as_slice() => return true,
+            Some(previous) => previous.to_vec(),
+            None => {
+                self.learned_keys
+                    .insert(commit.signer_id, commit.public_key.clone());
+                return true;
+            }
+        };
 
-        info!(
-            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tsign and broadcast",
+        let accepted = self.config.key_change_policy() == KeyChangePolicy::Accept;
+        warn!(
+            "Slot {}\tBounce Unit {}\tKeyChanged: signer {} presented a new public key, {}",
             self.slot_info.i,
             self.id,
-            commit.typ(),
+            commit.signer_id,
+            if accepted { "accepted" } else { "rejected" },
         );
+        if accepted {
+            self.learned_keys
+                .insert(commit.signer_id, commit.public_key.clone());
+        }
+        self.emit(Event::KeyChanged {
+            signer_id: commit.signer_id,
+            previous,
+            public_key: commit.public_key.clone(),
+            accepted,
+        });
+        accepted
+    }
 
-        commit
+    pub fn registry(&self) -> Option<&PeerRegistry> {
+        self.registry.as_ref()
     }
 
-    async fn process(&mut self, mut commit: Commit) {
-        if self.public_key == commit.public_key {
-            return;
+    /// The registry, e.g. to mark peers as faulty.
+    pub fn registry_mut(&mut self) -> Option<&mut PeerRegistry> {
+        self.registry.as_mut()
+    }
+
+    // Number of Bounce units in the constellation.
+    fn num_cubesats(&self) -> usize {
+        match &self.registry {
+            Some(registry) => registry.len(),
+            None => self.config.num_cubesats as usize,
         }
+    }
 
-        if self.slot_info.phase == Phase::Stop {
-            return;
+    // Number of signatures needed to aggregate.
+    fn threshold(&self) -> usize {
+        self.config.required_signers(self.num_cubesats())
+    }
+
+    // Whether enough Bounce units not known to be faulty remain to reach supermajority.
+    fn quorum_reachable(&self) -> bool {
+        self.num_reachable() >= self.threshold()
+    }
+
+    // Number of Bounce units not known to be faulty. Without a registry, all are assumed to be.
+    fn num_reachable(&self) -> usize {
+        match &self.registry {
+            Some(registry) => registry.num_reachable(),
+            None => self.num_cubesats(),
         }
+    }
 
-        // If thie Bounce unit has already aggregated or received an aggregate signature, then just
-        // return.
-        if self.slot_info.aggregated {
-            return;
+    // Aggregate commit of the collected commits of a type, given their aggregate signature and
+    // public key.
+    fn aggregate_of_collected(
+        &self,
+        commit_type: CommitType,
+        aggregate_signature: Vec<u8>,
+        aggregate_public_key: Vec<u8>,
+    ) -> Commit {
+        // The aggregate carries the exact message the signers signed, so that it can be verified
+        // without reconstructing the message from i and j.
+        let commits = self.get_commits(commit_type);
+        let msg = commits[0].msg.clone();
+        let payload = commits[0].payload.clone();
+        let justification = commits[0].justification.clone();
+        let mut signers: Vec<u32> = commits.iter().map(|c| c.signer_id).collect();
+        signers.sort_unstable();
+
+        let i = self.slot_info.i;
+        let commit = Commit {
+            typ: commit_type.into(),
+            i,
+            // The last committed index, for precommit it's equal to i.
+            j: if commit_type == CommitType::Precommit {
+                i
+            } else {
+                self.slot_info.j
+            },
+            aggregated: true,
+            public_key: aggregate_public_key,
+            msg,
+            signature: aggregate_signature,
+            signer_id: self.id as u32,
+            signer_count: signers.len() as u32,
+            signers,
+            payload,
+            partial: false,
+            justification,
+            version: SIGNATURE_SCHEME_VERSION,
+        };
+
+        if !self.config.uncompressed_points {
+            return commit;
         }
+        // Aggregation yields valid points, which always decompress.
+        with_uncompressed_points(&commit).unwrap_or(commit)
+    }
 
-        // If the commit is an aggregate signature, then we note that this slot is aggregated and
-        // update the last committed slot and current slot information.
-        if commit.aggregated && commit.i == self.slot_info.i {
-            self.slot_info.aggregated = true;
-            self.slot_info.j = commit.j;
+    // Broadcasts a partial aggregate of the collected commits of a type whenever their number
+    // reaches a multiple of `config.checkpoint_every`, until the slot finalizes.
+    async fn checkpoint_if_due(&mut self, commit_type: CommitType) {
+        let every = self.config.checkpoint_every as usize;
+        let collected = self.get_commits(commit_type).len();
+        if every == 0
+            || collected == 0
+            || collected % every != 0
+            || self.slot_info.aggregated
+            || self.is_observer()
+        {
             return;
         }
 
-        match self.slot_info.phase {
-            Phase::First => {
-                // Phase 1 only handles precommits
-                if commit.typ() == CommitType::Precommit {
-                    if !self.slot_info.signed {
-                        commit = self.sign_and_broadcast(commit).await;
-                    }
-
-                    // Now, the precommit is the one signed by me or other cubesats.
-                    self.slot_info.precommits.push(commit.clone());
+        let (aggregate_signature, aggregate_public_key) =
+            match Cubesat::aggregate(self.get_commits(commit_type)) {
+                Ok(aggregate) => aggregate,
+                Err(e) => {
+                    warn!(
+                        "Slot {}\tBounce Unit {}\tCommit Type {:?}\tfailed to checkpoint: {}",
+                        self.slot_info.i, self.id, commit_type, e,
+                    );
+                    return;
                 }
+            };
+        let checkpoint = Commit {
+            partial: true,
+            ..self.aggregate_of_collected(commit_type, aggregate_signature, aggregate_public_key)
+        };
+
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tcheckpoint of {} signatures",
+            self.slot_info.i, self.id, commit_type, collected,
+        );
+        self.send_result(checkpoint).await;
+    }
+
+    async fn aggregate_and_broadcast(&mut self, commit_type: CommitType) {
+        let mut result = Cubesat::aggregate(self.get_commits(commit_type));
+
+        if let Err(AggregateError::Bls(_)) = result {
+            // A malformed commit made it into the collected set. Set aside the invalid ones and
+            // retry if the valid ones still reach supermajority.
+            let (valid, invalid) = partition_valid(self.get_commits(commit_type));
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tdiscarding {} invalid commits",
+                self.slot_info.i,
+                self.id,
+                commit_type,
+                invalid.len(),
+            );
+            let enough = valid.len() >= self.threshold();
+            *self.get_commits_mut(commit_type) = valid;
+            if enough {
+                result = Cubesat::aggregate(self.get_commits(commit_type));
             }
-            Phase::Second => {
-                // Sign
-                if !self.slot_info.signed {
-                    commit = self.sign_and_broadcast(commit).await;
-                }
+        }
 
-                if commit.typ() == CommitType::Precommit {
-                    self.slot_info.precommits.push(commit.clone());
-                } else if commit.typ() == CommitType::Noncommit {
-                    self.slot_info.noncommits.push(commit.clone());
-                }
+        if let Err(AggregateError::MessageMismatch { .. }) = result {
+            // The collected set was split between messages. Keep the commits signing the message
+            // most of them agree on, and retry if they still reach supermajority.
+            let (agreeing, outliers) = split_by_majority_message(self.get_commits(commit_type));
+            for outlier in outliers.iter() {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tMessageMismatch: discarding commit from {}",
+                    self.slot_info.i,
+                    self.id,
+                    commit_type,
+                    outlier.signer_id,
+                );
+                self.metrics.inc_commits_dropped();
             }
-            Phase::Third => {
-                // At the beginning of the Phase 3, this Bounce unit has signed and broadcast
-                // a noncommit, so it will only listen to others' commits.
-                if commit.typ() == CommitType::Precommit {
-                    self.slot_info.precommits.push(commit.clone());
-                } else if commit.typ() == CommitType::Noncommit {
-                    self.slot_info.noncommits.push(commit.clone());
-                }
+            let enough = agreeing.len() >= self.threshold();
+            *self.get_commits_mut(commit_type) = agreeing;
+            if enough {
+                result = Cubesat::aggregate(self.get_commits(commit_type));
             }
-            Phase::Stop => {
-                unreachable!("Handled Stop phase earlier in the function.");
+        }
+
+        let (aggregate_signature, aggregate_public_key) = match result {
+            Ok(aggregate) => aggregate,
+            Err(e) => {
+                error!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tfailed to aggregate: {}",
+                    self.slot_info.i, self.id, commit_type, e,
+                );
+                return;
             }
+        };
+
+        let commit =
+            self.aggregate_of_collected(commit_type, aggregate_signature, aggregate_public_key);
+
+        // Never ship a broken aggregate to the whole constellation.
+        if let Err(error) = verify_aggregate(&commit, self.threshold()) {
+            error!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tSelfAggregateInvalid: {}",
+                self.slot_info.i, self.id, commit_type, error,
+            );
+            self.emit(Event::SelfAggregateInvalid {
+                i: self.slot_info.i,
+                typ: commit_type,
+                error,
+            });
+            return;
         }
 
-        if self.slot_info.precommits.len() >= supermajority(self.num_cubesats as usize) {
-            self.aggregate_and_broadcast(commit).await;
-        } else if self.slot_info.noncommits.len() >= supermajority(self.num_cubesats as usize) {
-            self.aggregate_and_broadcast(commit).await;
+        self.finalize(commit.clone());
+        self.metrics.inc_aggregations();
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\taggregated and broadcast",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+        );
+        self.send_result(commit).await;
+        if self.config.aggregate_max_retries > 0 {
+            self.aggregate_retry = Some((
+                Instant::now() + self.min_aggregate_interval(),
+                self.config.aggregate_max_retries,
+            ));
         }
     }
 
-    pub async fn run(&mut self) {
-        loop {
-            tokio::select! {
-                Ok(phase) = self.timer_rx.recv() => {
-                    match phase {
-                        Phase::First => {
-                            self.slot_info.next();
-                            info!(
-                                "Slot {}\tBounce Unit {}\tFirst Phase Starts",
-                                self.slot_info.i,
-                                self.id,
-                            );
-                        }
-                        Phase::Second => {
-                        }
-                        Phase::Third => {
-                            if !self.slot_info.signed {
-                                // Sign and broadcast noncommit for (j+1, i)
-
-                                let msg = format!("noncommit({}, {})", self.slot_info.j + 1, self.slot_info.i);
-
-                                let noncommit = Commit {
-                                    typ: CommitType::Noncommit.into(),
-                                    i: self.slot_info.i,
-                                    j: self.slot_info.j,
-                                    msg: msg.clone().into_bytes(),
-                                    public_key: self.public_key.clone(),
-                                    signature: Bn256.sign(&self.private_key, &msg.as_bytes()).unwrap(),
-                                    aggregated: false,
-                                    signer_id: self.id as u32,
-                                };
-                                self.sign_and_broadcast(
-                                    noncommit.clone()
-                                ).await;
-                                self.slot_info.noncommits.push(noncommit);
-                            }
-                        }
-                        Phase::Stop => {
-
-                        }
+    // Sends a commit to the communications hub without blocking the run loop. If the channel is
+    // full, the commit is handed over to a separate task which retries for a short while, so that
+    // phase transitions and commands keep being serviced in the meantime.
+    async fn send_result(&mut self, commit: Commit) {
+        match self.result_tx.try_send(commit) {
+            Ok(()) => {}
+            Err(TrySendError::Full(commit)) => {
+                self.metrics.inc_results_deferred();
+                let result_tx = self.result_tx.clone();
+                let (i, id) = (self.slot_info.i, self.id);
+                tokio::spawn(async move {
+                    let sent = tokio::time::timeout(RESULT_RETRY_TIMEOUT, result_tx.send(commit));
+                    if !matches!(sent.await, Ok(Ok(()))) {
+                        warn!(
+                            "Slot {}\tBounce Unit {}\tdropped a result for the communications hub",
+                            i, id,
+                        );
                     }
-                    self.slot_info.phase = phase;
-                }
-                Some(commit) = self.request_rx.recv() => {
-                    self.process(commit).await;
-                }
+                });
+            }
+            Err(TrySendError::Closed(_)) => {
+                error!(
+                    "Slot {}\tBounce Unit {}\tcommunications hub is gone",
+                    self.slot_info.i, self.id,
+                );
+                self.result_closed = true;
             }
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use bls_signatures_rs::MultiSignature;
+    // Interval between two heartbeats, if they're enabled.
+    pub(crate) fn heartbeat_interval(&self) -> Option<Duration> {
+        match self.config.heartbeat_interval_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms as u64)),
+        }
+    }
 
-    #[tokio::test]
-    async fn cubesat_sign_aggregate() {
-        let (result_tx, mut result_rx) = mpsc::channel(1);
-        let (request_tx, request_rx) = mpsc::channel(15);
-        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+    // Signs and broadcasts a heartbeat carrying the current slot. Unlike precommits and
+    // noncommits, it doesn't count as signing for the slot.
+    pub(crate) async fn send_heartbeat(&mut self) {
+        if self.is_observer() {
+            return;
+        }
 
-        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx);
-        c.slot_info.phase = Phase::First;
+        let msg = heartbeat_message(&self.config.domain, self.slot_info.i);
+        let signature = match self.sign_with_retry(&msg).await {
+            Ok(signature) => signature,
+            Err(e) => {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tfailed to sign heartbeat: {}",
+                    self.slot_info.i, self.id, e,
+                );
+                return;
+            }
+        };
+        let heartbeat = Commit {
+            typ: CommitType::Heartbeat.into(),
+            i: self.slot_info.i,
+            j: self.slot_info.j,
+            msg,
+            public_key: self.public_key.as_bytes().to_vec(),
+            signature,
+            signer_id: self.id as u32,
+            version: SIGNATURE_SCHEME_VERSION,
+            ..Default::default()
+        };
+        self.send_result(heartbeat).await;
+    }
 
-        tokio::spawn(async move {
-            c.run().await;
-        });
+    fn sign_max_attempts(&self) -> u32 {
+        match self.config.sign_max_attempts {
+            0 => DEFAULT_SIGN_MAX_ATTEMPTS,
+            n => n,
+        }
+    }
 
-        let msg = "hello".as_bytes().to_vec();
+    // Signs a message, retrying with exponential backoff while the signer reports transient
+    // errors.
+    async fn sign_with_retry(&self, msg: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let max_attempts = self.sign_max_attempts();
+        let mut delay = SIGN_RETRY_BASE_DELAY;
+        let mut attempt = 1;
 
-        let mut rng = thread_rng();
-        let ground_station_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let ground_station_public_key = Bn256
-            .derive_public_key(&ground_station_private_key)
-            .unwrap();
-        let signature = Bn256.sign(&ground_station_private_key, &msg).unwrap();
+        loop {
+            match self.signer.sign(msg) {
+                Err(e) if e.is_transient() && attempt < max_attempts => {
+                    warn!(
+                        "Slot {}\tBounce Unit {}\tsigning attempt {} of {} failed: {}",
+                        self.slot_info.i, self.id, attempt, max_attempts, e,
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
 
-        let precommit = Commit {
-            typ: CommitType::Precommit.into(),
-            i: 0,
-            j: 0,
-            msg: msg.clone(),
-            public_key: ground_station_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
+    async fn sign_and_broadcast(&mut self, mut commit: Commit) -> Option<Commit> {
+        // Invariant 2: never sign once an aggregate was received or sent for the slot.
+        #[cfg(feature = "debug-invariants")]
+        {
+            if self.slot_info.aggregated {
+                error!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tInvariantViolation: signing after aggregation",
+                    self.slot_info.i,
+                    self.id,
+                    commit.typ(),
+                );
+                return None;
+            }
+        }
+
+        commit.signature = match self.sign_with_retry(&commit.msg).await {
+            Ok(signature) => signature,
+            Err(e) => {
+                error!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tfailed to sign: {}",
+                    self.slot_info.i,
+                    self.id,
+                    commit.typ(),
+                    e,
+                );
+                return None;
+            }
+        };
+        commit.public_key = self.public_key.as_bytes().to_vec();
+        commit.i = self.slot_info.i;
+        commit.signer_id = self.id as u32;
+        commit.version = SIGNATURE_SCHEME_VERSION;
+
+        self.slot_info.signed = true;
+        self.signed_count += 1;
+        self.metrics.inc_signatures();
+        self.send_result(commit.clone()).await;
+
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tsign and broadcast",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+        );
+
+        Some(commit)
+    }
+
+    // Checks that the commit was signed for this constellation and that its signature is valid.
+    // The signature is only checked here if it wasn't already, as given by `signature_valid`.
+    fn verify(&self, commit: &Commit, signature_valid: Option<bool>) -> Result<(), DropReason> {
+        if !in_domain(&self.config.domain, &commit.msg) {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tmessage from another domain",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+            );
+            return Err(DropReason::WrongDomain);
+        }
+
+        let signature_valid = signature_valid.unwrap_or_else(|| {
+            self.slot_info.signature_cache.contains(commit) || (self.signature_verifier)(commit)
+        });
+        if !signature_valid {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tinvalid signature from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.signer_id,
+            );
+            return Err(DropReason::InvalidSignature);
+        }
+
+        Ok(())
+    }
+
+    // Checks the signatures of commits on `config.verify_threads` blocking threads, so that the
+    // task running this Bounce unit isn't blocked. Returns None when verification is left to
+    // `collect`.
+    async fn verify_offloaded(&self, commits: &[Commit]) -> Option<Vec<bool>> {
+        match self.config.verify_threads {
+            0 => None,
+            threads => Some(
+                verify_signatures_with(commits, threads as usize, self.signature_verifier.clone())
+                    .await,
+            ),
+        }
+    }
+
+    // Checks that an aggregate of a peer represents enough distinct signers and, with a registry,
+    // that its public key is the aggregate of their registered keys. Without a registry the
+    // aggregate public key it carries is taken on trust, see `verify_aggregate`.
+    fn verify_aggregate_signers(&self, commit: &Commit) -> Result<(), VerifyError> {
+        verify_aggregate(commit, self.threshold())?;
+
+        if let Some(registry) = &self.registry {
+            let public_keys = commit
+                .signers
+                .iter()
+                .map(|&id| registry.get(id))
+                .collect::<Option<Vec<&[u8]>>>()
+                .ok_or(VerifyError::PublicKeyMismatch)?;
+            verify_aggregate_against(commit, &public_keys)?;
+        }
+        Ok(())
+    }
+
+    pub fn metrics(&self) -> CubesatMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Reader of the metrics of this Bounce unit which can be moved to another task, e.g. one
+    /// serving a metrics endpoint.
+    pub fn metrics_handle(&self) -> MetricsHandle {
+        self.metrics.clone()
+    }
+
+    /// Metrics of this Bounce unit in the Prometheus text exposition format.
+    pub fn metrics_prometheus(&self) -> String {
+        self.metrics.to_prometheus(self.id)
+    }
+
+    /// Replaces the signer of this Bounce unit, e.g. with one backed by an HSM.
+    pub fn set_signer(&mut self, signer: Box<dyn Signer>) {
+        self.public_key = signer.public_key();
+        self.signer = signer;
+    }
+
+    /// Public key of this Bounce unit, e.g. to register it with its peers.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Whether this Bounce unit only observes the protocol, without signing or broadcasting.
+    pub fn is_observer(&self) -> bool {
+        self.config.observer
+    }
+
+    /// How long this Bounce unit waits after entering phase 3 before signing its own noncommit.
+    pub fn clock_skew_tolerance(&self) -> Duration {
+        Duration::from_millis(self.config.clock_skew_tolerance_ms as u64)
+    }
+
+    /// How long after starting this Bounce unit refrains from signing phase 3 noncommits.
+    pub fn startup_grace(&self) -> Duration {
+        Duration::from_millis(self.config.startup_grace_ms as u64)
+    }
+
+    // Whether this Bounce unit started too recently to sign a phase 3 noncommit.
+    fn in_startup_grace(&self) -> bool {
+        Instant::now() < self.started_at + self.startup_grace()
+    }
+
+    /// Numbers of distinct signers of the precommits and noncommits collected so far this slot,
+    /// in that order, e.g. to watch progress towards a supermajority.
+    pub fn collected_signers(&self) -> (usize, usize) {
+        let distinct = |commits: &[Commit]| {
+            commits
+                .iter()
+                .map(|c| c.public_key.as_slice())
+                .collect::<HashSet<_>>()
+                .len()
+        };
+        (
+            distinct(&self.slot_info.precommits),
+            distinct(&self.slot_info.noncommits),
+        )
+    }
+
+    /// Bytes held by the signatures, public keys and messages of the precommits and noncommits
+    /// collected so far this slot, e.g. to watch the memory used on a constrained satellite.
+    pub fn memory_footprint(&self) -> usize {
+        self.slot_info
+            .precommits
+            .iter()
+            .chain(self.slot_info.noncommits.iter())
+            .map(|c| c.signature.len() + c.public_key.len() + c.msg.len())
+            .sum()
+    }
+
+    /// Aggregate signature, aggregate public key and signer count of the aggregate this Bounce
+    /// unit would emit if aggregation triggered now, from the precommits collected so far or else
+    /// the noncommits. None if nothing was collected. Nothing is finalized or broadcast.
+    pub fn current_aggregate_preview(&self) -> Option<(Vec<u8>, Vec<u8>, usize)> {
+        let commits = if !self.slot_info.precommits.is_empty() {
+            &self.slot_info.precommits
+        } else if !self.slot_info.noncommits.is_empty() {
+            &self.slot_info.noncommits
+        } else {
+            return None;
+        };
+
+        let (aggregate_signature, aggregate_public_key) = Cubesat::aggregate(commits).ok()?;
+        Some((aggregate_signature, aggregate_public_key, commits.len()))
+    }
+
+    /// Index of the current slot.
+    pub fn slot(&self) -> u32 {
+        self.slot_info.i
+    }
+
+    pub fn phase(&self) -> &Phase {
+        &self.slot_info.phase
+    }
+
+    /// Outcomes of the most recent slots.
+    pub fn history(&self) -> &SlotHistory {
+        &self.slot_info.history
+    }
+
+    /// Indices of slots within the history which never finalized, e.g. to request their
+    /// aggregates from peers after recovering from a partition.
+    pub fn unfinalized_slots(&self) -> Vec<u32> {
+        self.slot_info.history.unfinalized()
+    }
+
+    /// Returns this Bounce unit to the state it was created in, before slot 1, so that it can be
+    /// reused without generating a new key pair. The config, keys, channels and hooks are kept,
+    /// as are the metrics, which keep counting across resets.
+    pub fn reset(&mut self) {
+        self.slot_info = SlotInfo::new();
+        self.result_closed = false;
+        self.paused = false;
+        self.slot_frozen = false;
+        self.started_at = Instant::now();
+        self.slot_started = None;
+        self.last_aggregate = None;
+        self.noncommit_at = None;
+        self.aggregate_retry = None;
+        self.deferred.clear();
+        self.learned_keys = PeerRegistry::new();
+        self.violation = None;
+        self.terminating = false;
+        // Verdicts of commits still being verified belong to the previous run.
+        while self.verified_rx.try_recv().is_ok() {}
+    }
+
+    /// Processes a commit received from the communications hub, signing and aggregating as the
+    /// protocol requires.
+    pub async fn process(&mut self, commit: Commit) -> ProcessOutcome {
+        let signature_valid = if self.slot_info.signature_cache.contains(&commit) {
+            Some(true)
+        } else {
+            self.verify_offloaded(std::slice::from_ref(&commit))
+                .await
+                .map(|valid| valid[0])
+        };
+        self.process_verified(commit, signature_valid).await
+    }
+
+    // Same as `process`, for a commit whose signature may already have been checked.
+    async fn process_verified(
+        &mut self,
+        commit: Commit,
+        signature_valid: Option<bool>,
+    ) -> ProcessOutcome {
+        // Counting signatures rather than checking `slot_info.signed` leaves out a signature which
+        // was only found to be recorded already.
+        let signed_count = self.signed_count;
+        let aggregated = self.slot_info.aggregated;
+
+        let commit_type = commit.typ();
+        let dropped = match self.collect(commit, signature_valid).await {
+            Ok(()) => {
+                self.aggregate_if_supermajority().await;
+                self.checkpoint_if_due(commit_type).await;
+                None
+            }
+            Err(reason) => Some(reason),
+        };
+
+        ProcessOutcome {
+            signed: self.signed_count > signed_count,
+            aggregated: !aggregated && self.slot_info.aggregated,
+            dropped,
+        }
+    }
+
+    // Checks the signature of a commit received by `run` on a blocking thread when verification
+    // is offloaded, so that the run loop keeps handling phase transitions and commands meanwhile.
+    // The commit is processed once the verdict comes back through `verified_rx`, against the slot
+    // state at that time, so a commit of a slot which ended meanwhile is dropped as such.
+    fn verify_concurrently(&self, commit: Commit) {
+        let signature_verifier = self.signature_verifier.clone();
+        let verified_tx = self.verified_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let valid = signature_verifier(&commit);
+            // The receiver lives as long as this Bounce unit.
+            let _ = verified_tx.send((commit, valid));
+        });
+    }
+
+    /// Decodes a commit from its protobuf encoding and processes it. This is the entry point for
+    /// raw transports, and for fuzzing, as malformed input is dropped rather than panicking.
+    pub async fn process_bytes(&mut self, raw: &[u8]) -> ProcessOutcome {
+        match Commit::decode(raw) {
+            Ok(commit) => self.process(commit).await,
+            Err(e) => {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tfailed to decode commit: {}",
+                    self.slot_info.i, self.id, e,
+                );
+                self.metrics.inc_commits_dropped();
+                ProcessOutcome {
+                    dropped: Some(DropReason::Decode),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    /// Processes commits delivered at once by the communications hub. Supermajority is checked
+    /// once all of them are collected, so at most one aggregate is emitted for the batch.
+    pub async fn process_batch(&mut self, commits: Vec<Commit>) {
+        let valid = self.verify_offloaded(&commits).await;
+        let yield_every = self.verify_yield_every();
+
+        let mut collected = false;
+        for (k, commit) in commits.into_iter().enumerate() {
+            // Signatures which weren't offloaded are checked on this task, which gives the other
+            // tasks of the runtime a chance to run in between.
+            if valid.is_none() && k > 0 && k % yield_every == 0 {
+                tokio::task::yield_now().await;
+            }
+
+            let signature_valid = valid.as_ref().map(|valid| valid[k]);
+            let commit_type = commit.typ();
+            if self.collect(commit, signature_valid).await.is_ok() {
+                collected = true;
+                self.checkpoint_if_due(commit_type).await;
+            }
+        }
+
+        if collected {
+            self.aggregate_if_supermajority().await;
+        }
+    }
+
+    // Whether a commit from the same signer was already collected for this slot. A commit of a
+    // collected signer id under another key is a key change, left to `learn_key`.
+    fn is_duplicate(&self, commit: &Commit) -> bool {
+        self.get_commits(commit.typ())
+            .iter()
+            .any(|c| c.public_key == commit.public_key)
+    }
+
+    // Stores a commit among the precommits or noncommits of this slot, unless its signer was
+    // already collected or it signs another message than the collected ones, as their signatures
+    // couldn't be aggregated together. The commit of a signer whose key change was accepted
+    // replaces the one signed with its previous key, so that the signer is counted once.
+    fn push_commit(&mut self, commit: Commit) -> Result<(), DropReason> {
+        if self.is_duplicate(&commit) {
+            return Err(DropReason::Duplicate);
+        }
+
+        if let Some(collected) = self.get_commits(commit.typ()).first() {
+            if collected.msg != commit.msg {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tmessage from {} differs from collected ones",
+                    self.slot_info.i,
+                    self.id,
+                    commit.typ(),
+                    commit.signer_id,
+                );
+                return Err(DropReason::MessageMismatch);
+            }
+        }
+
+        let commit_type = commit.typ();
+        let commits = self.get_commits_mut(commit_type);
+        if let Some(rotated) = commits.iter_mut().find(|c| c.signer_id == commit.signer_id) {
+            *rotated = commit;
+            return Ok(());
+        }
+        commits.push(commit);
+        if self.get_commits(commit_type).len() == self.threshold() {
+            self.report_quorum(commit_type);
+        }
+        Ok(())
+    }
+
+    // Reports that the commits of a type collected this slot just reached supermajority.
+    fn report_quorum(&self, commit_type: CommitType) {
+        let elapsed = self
+            .slot_started
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tQuorumReached {:?} after the slot started",
+            self.slot_info.i, self.id, commit_type, elapsed,
+        );
+        self.emit(Event::QuorumReached {
+            i: self.slot_info.i,
+            phase: self.slot_info.phase.clone(),
+            elapsed_since_slot_start: elapsed,
+        });
+    }
+
+    // Verifies and collects a commit, signing one if this Bounce unit hasn't signed yet for this
+    // slot. Accepting an aggregate for this slot also counts as collecting it.
+    async fn collect(
+        &mut self,
+        mut commit: Commit,
+        signature_valid: Option<bool>,
+    ) -> Result<(), DropReason> {
+        if let Some(commit_log) = &mut self.commit_log {
+            if let Err(e) = commit_log.append(&commit) {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tfailed to log commit: {}",
+                    self.slot_info.i, self.id, e,
+                );
+            }
+        }
+
+        if self.public_key.as_bytes() == commit.public_key.as_slice() {
+            return Err(DropReason::OwnCommit);
+        }
+
+        self.metrics.inc_commits_received();
+
+        // `typ()` would read it as a precommit.
+        if commit.checked_typ().is_none() {
+            warn!(
+                "Slot {}\tBounce Unit {}\tunknown commit type {} from {}",
+                self.slot_info.i, self.id, commit.typ, commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::UnknownType);
+        }
+
+        if !commit.version_supported() {
+            warn!(
+                "Slot {}\tBounce Unit {}\tunsupported signature scheme version {} from {}",
+                self.slot_info.i, self.id, commit.version, commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::UnsupportedVersion);
+        }
+
+        if commit.typ() == CommitType::Heartbeat {
+            return Err(DropReason::Heartbeat);
+        }
+
+        // Phase transitions are only applied between commits by the run loop, but the commit is
+        // still handled under the phase it arrived in even if a transition is pending while this
+        // Bounce unit awaits its signer.
+        let phase = self.slot_info.phase.clone();
+        if phase == Phase::Stop {
+            return Err(DropReason::Stopped);
+        }
+
+        if self.slot_info.aborted {
+            return Err(DropReason::SlotAborted);
+        }
+
+        if commit.partial {
+            return Err(DropReason::PartialAggregate);
+        }
+
+        if commit.msg.is_empty() && !self.config.allow_empty_message {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tempty message from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::EmptyMessage);
+        }
+
+        // If thie Bounce unit has already aggregated or received an aggregate signature, then just
+        // return.
+        if self.slot_info.aggregated {
+            self.check_acknowledged(&commit);
+            if commit.aggregated && commit.i == self.slot_info.i {
+                if self.adopt_canonical(&commit) {
+                    self.rebroadcast_aggregate().await;
+                } else {
+                    self.check_conflict(&commit);
+                }
+            } else if !commit.aggregated {
+                // The commit may be a redelivery of the one which triggered aggregation, in case
+                // the aggregate never reached the communications hub.
+                self.rebroadcast_aggregate().await;
+            }
+            return Err(DropReason::AlreadyAggregated);
+        }
+
+        if !commit.aggregated && self.is_duplicate(&commit) {
+            self.check_equivocation(&commit);
+            return Err(DropReason::Duplicate);
+        }
+
+        if !commit.aggregated && !self.message_allowed(&commit) {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tNonCanonicalMessage: message isn't the one of slots ({}, {}) from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.j as u64 + 1,
+                commit.i,
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::NonCanonicalMessage);
+        }
+
+        if let Err(reason) = self.verify(&commit, signature_valid) {
+            self.metrics.inc_commits_dropped();
+            if reason == DropReason::InvalidSignature {
+                self.protocol_violation(ProtocolViolation::InvalidSignature {
+                    signer_id: commit.signer_id,
+                });
+            }
+            return Err(reason);
+        }
+        self.slot_info.signature_cache.insert(&commit);
+
+        if !commit.aggregated && commit.i != self.slot_info.i {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tSlotMismatch: commit for slot {} from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.i,
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            self.protocol_violation(ProtocolViolation::SlotMismatch {
+                signer_id: commit.signer_id,
+                i: commit.i,
+            });
+            return Err(if self.violation.is_some() {
+                DropReason::Stopped
+            } else {
+                DropReason::SlotMismatch
+            });
+        }
+        if self.violation.is_some() {
+            return Err(DropReason::Stopped);
+        }
+
+        if !commit.aggregated && !self.justification_valid(&commit) {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tinvalid justification from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::InvalidJustification);
+        }
+
+        if let Some(msg_validator) = &self.msg_validator {
+            if !msg_validator(&commit.msg) {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tmessage from {} rejected by validator",
+                    self.slot_info.i,
+                    self.id,
+                    commit.typ(),
+                    commit.signer_id,
+                );
+                self.metrics.inc_commits_dropped();
+                return Err(DropReason::InvalidMessage);
+            }
+        }
+
+        if !commit.aggregated && !self.learn_key(&commit) {
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::KeyChanged);
+        }
+
+        // An aggregate of another slot can't finalize this one, and isn't a commit to collect.
+        if commit.aggregated && commit.i != self.slot_info.i {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tSlotMismatch: aggregate for slot {} from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.i,
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return Err(DropReason::SlotMismatch);
+        }
+
+        // If the commit is an aggregate signature, then we note that this slot is aggregated and
+        // update the last committed slot and current slot information.
+        if commit.aggregated {
+            if let Err(e) = self.verify_aggregate_signers(&commit) {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tCommit Type {:?}\tinvalid aggregate from {}: {}",
+                    self.slot_info.i,
+                    self.id,
+                    commit.typ(),
+                    commit.signer_id,
+                    e,
+                );
+                self.metrics.inc_commits_dropped();
+                return Err(match e {
+                    VerifyError::InsufficientSigners { .. }
+                    | VerifyError::SignerCountMismatch { .. } => DropReason::InsufficientSigners,
+                    _ => DropReason::InvalidSignature,
+                });
+            }
+
+            self.finalize(commit);
+            return Ok(());
+        }
+
+        match phase {
+            Phase::First => {
+                // Phase 1 only handles precommits
+                if commit.typ() != CommitType::Precommit {
+                    self.report_desync(&commit);
+                    return Err(DropReason::WrongPhase);
+                }
+
+                if !self.slot_info.signed && !self.is_observer() {
+                    if let Some(signed) = self.sign_and_broadcast(commit.clone()).await {
+                        commit = signed;
+                    }
+                }
+
+                // Now, the precommit is the one signed by me or other cubesats.
+                self.push_commit(commit)
+            }
+            Phase::Second => {
+                // A Bounce unit which hasn't signed yet, e.g. because it was offline during phase
+                // 1, joins late by signing the first commit it sees, whatever its type.
+                if !self.slot_info.signed && !self.is_observer() {
+                    match self.own_collected_type() {
+                        None => {
+                            info!(
+                                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tLateJoin: signing in phase 2",
+                                self.slot_info.i,
+                                self.id,
+                                commit.typ(),
+                            );
+                            if let Some(signed) = self.sign_and_broadcast(commit.clone()).await {
+                                commit = signed;
+                            }
+                        }
+                        // A signature of this Bounce unit was recorded without the slot being
+                        // marked as signed. Don't sign again, as signing the other type would
+                        // break invariant 1.
+                        Some(typ) => {
+                            warn!(
+                                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tLateJoin: already recorded a signed {:?}",
+                                self.slot_info.i,
+                                self.id,
+                                commit.typ(),
+                                typ,
+                            );
+                            self.slot_info.signed = true;
+                        }
+                    }
+                }
+
+                self.push_commit(commit)
+            }
+            Phase::Third => {
+                // At the beginning of the Phase 3, this Bounce unit has signed and broadcast
+                // a noncommit, so it will only listen to others' commits.
+                self.push_commit(commit)
+            }
+            Phase::Stop => {
+                unreachable!("Handled Stop phase earlier in the function.");
+            }
+        }
+    }
+
+    // Type of the commit of this Bounce unit among those collected for this slot, if any.
+    fn own_collected_type(&self) -> Option<CommitType> {
+        let own = |c: &Commit| c.public_key == self.public_key.as_bytes();
+        if self.slot_info.precommits.iter().any(own) {
+            Some(CommitType::Precommit)
+        } else if self.slot_info.noncommits.iter().any(own) {
+            Some(CommitType::Noncommit)
+        } else {
+            None
+        }
+    }
+
+    // Whether a commit signs its canonical slot message or, unless `config.canonical_messages_only`,
+    // a free-form message such as those of a ground station.
+    fn message_allowed(&self, commit: &Commit) -> bool {
+        is_canonical_message(&self.config.domain, commit)
+            || (!self.config.canonical_messages_only
+                && !is_slot_message(&self.config.domain, &commit.msg))
+    }
+
+    // Checks that the justification of a commit, if any, is a valid precommit aggregate for slot j
+    // whose digest is folded into the signed message.
+    fn justification_valid(&self, commit: &Commit) -> bool {
+        if commit.justification.is_empty() {
+            return true;
+        }
+        if commit.typ() != CommitType::Precommit
+            || !is_justified_by(&commit.msg, &commit.justification)
+        {
+            return false;
+        }
+
+        match Commit::decode(commit.justification.as_slice()) {
+            Ok(aggregate) => {
+                aggregate.typ() == CommitType::Precommit
+                    && !aggregate.partial
+                    && aggregate.i == commit.j
+                    && verify_aggregate(&aggregate, self.threshold()).is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Whether the time spent processing commits this slot exceeds `config.slot_cpu_budget_ms`.
+    fn over_budget(&self) -> bool {
+        match self.config.slot_cpu_budget_ms {
+            0 => false,
+            ms => self.slot_info.processing >= Duration::from_millis(ms as u64),
+        }
+    }
+
+    // Sets a received commit aside until nothing else is pending, as the slot processing budget
+    // is exhausted.
+    fn defer(&mut self, commit: Commit) {
+        if self.deferred.len() >= DEFERRED_COMMITS_CAPACITY {
+            warn!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tdeferred commits full, dropping commit from {}",
+                self.slot_info.i,
+                self.id,
+                commit.typ(),
+                commit.signer_id,
+            );
+            self.metrics.inc_commits_dropped();
+            return;
+        }
+        self.metrics.inc_commits_deferred();
+        self.deferred.push_back(commit);
+    }
+
+    // Processes a commit handed over by a `CubesatPool`, counting the time spent against the slot
+    // processing budget, or sets it aside once the budget is exhausted.
+    pub(crate) async fn receive(&mut self, commit: Commit) {
+        if self.over_budget() {
+            self.defer(commit);
+            return;
+        }
+        let start = std::time::Instant::now();
+        self.process(commit).await;
+        self.slot_info.processing += start.elapsed();
+    }
+
+    // Whether commits set aside as the slot processing budget was exhausted wait to be processed.
+    pub(crate) fn has_deferred(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+
+    // Processes the oldest commit set aside by `receive`, for a `CubesatPool` which found nothing
+    // else pending.
+    pub(crate) async fn process_deferred(&mut self) {
+        if let Some(commit) = self.deferred.pop_front() {
+            let start = std::time::Instant::now();
+            self.process(commit).await;
+            self.slot_info.processing += start.elapsed();
+        }
+    }
+
+    // Verifies and processes a commit received by `run`, counting the time spent against the
+    // slot processing budget.
+    async fn handle_request(&mut self, commit: Commit) {
+        let start = std::time::Instant::now();
+        if self.slot_info.signature_cache.contains(&commit) {
+            self.process_verified(commit, Some(true)).await;
+        } else if self.config.verify_threads > 0 {
+            self.verify_concurrently(commit);
+        } else {
+            self.process(commit).await;
+        }
+        self.slot_info.processing += start.elapsed();
+    }
+
+    // Number of commits of a batch verified on this task between two yields.
+    fn verify_yield_every(&self) -> usize {
+        match self.config.verify_yield_every {
+            0 => DEFAULT_VERIFY_YIELD_EVERY,
+            n => n as usize,
+        }
+    }
+
+    // Minimum interval between two broadcasts of the aggregate of a slot.
+    fn min_aggregate_interval(&self) -> Duration {
+        match self.config.min_aggregate_interval_ms {
+            0 => AGGREGATE_REBROADCAST_INTERVAL,
+            ms => Duration::from_millis(ms as u64),
+        }
+    }
+
+    // Broadcasts the aggregate of this slot again, unless it was emitted or accepted less than
+    // the minimum aggregate interval ago.
+    async fn rebroadcast_aggregate(&mut self) {
+        if self.is_observer() {
+            return;
+        }
+
+        let i = self.slot_info.i;
+        if let Some((slot, at)) = self.last_aggregate {
+            if slot == i && at.elapsed() < self.min_aggregate_interval() {
+                return;
+            }
+        }
+
+        if let Some(aggregate) = self.slot_info.aggregate.clone() {
+            info!(
+                "Slot {}\tBounce Unit {}\tCommit Type {:?}\tre-broadcast aggregate",
+                i,
+                self.id,
+                aggregate.typ(),
+            );
+            self.last_aggregate = Some((i, Instant::now()));
+            self.send_result(aggregate).await;
+        }
+    }
+
+    // Stops retrying the broadcast of the own aggregate once a commit shows it was relayed: the
+    // communications hub echoing it back, or a peer committing the slot, be it with an agreeing
+    // aggregate or with a commit whose last committed slot is at least the current one.
+    fn check_acknowledged(&mut self, commit: &Commit) {
+        let accepted = match (&self.aggregate_retry, &self.slot_info.aggregate) {
+            (Some(_), Some(accepted)) => accepted,
+            _ => return,
+        };
+
+        let acknowledged = if commit.aggregated {
+            commit.i == self.slot_info.i && commit.typ == accepted.typ && commit.msg == accepted.msg
+        } else {
+            commit.j >= self.slot_info.i
+        };
+        if acknowledged {
+            info!(
+                "Slot {}\tBounce Unit {}\tAggregateAcknowledged: by {}",
+                self.slot_info.i, self.id, commit.signer_id,
+            );
+            self.aggregate_retry = None;
+        }
+    }
+
+    // When the broadcast of the own aggregate is to be retried, unless it was acknowledged.
+    pub(crate) fn aggregate_retry_at(&self) -> Option<Instant> {
+        self.aggregate_retry.map(|(at, _)| at)
+    }
+
+    // Broadcasts the own aggregate again if it's still unacknowledged once the retry is due.
+    pub(crate) async fn retry_aggregate_if_due(&mut self) {
+        let left = match self.aggregate_retry {
+            Some((at, left)) if at <= Instant::now() => left,
+            _ => return,
+        };
+        let aggregate = match self.slot_info.aggregate.clone() {
+            Some(aggregate) => aggregate,
+            None => {
+                self.aggregate_retry = None;
+                return;
+            }
+        };
+
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tAggregateRetry: re-broadcast, {} retries left",
+            self.slot_info.i,
+            self.id,
+            aggregate.typ(),
+            left - 1,
+        );
+        self.last_aggregate = Some((self.slot_info.i, Instant::now()));
+        self.send_result(aggregate).await;
+        self.aggregate_retry = if left > 1 {
+            Some((Instant::now() + self.min_aggregate_interval(), left - 1))
+        } else {
+            warn!(
+                "Slot {}\tBounce Unit {}\tAggregateUnacknowledged: giving up after {} retries",
+                self.slot_info.i, self.id, self.config.aggregate_max_retries,
+            );
+            None
+        };
+    }
+
+    // Adopts a valid aggregate for the current slot which agrees with the accepted one but comes
+    // first in the canonical order of `signer_bitmap`, e.g. when two supermajority subsets were
+    // both aggregated, so that every Bounce unit converges on the same aggregate. Returns whether
+    // it was adopted.
+    fn adopt_canonical(&mut self, commit: &Commit) -> bool {
+        let accepted = match &self.slot_info.aggregate {
+            Some(accepted) => accepted,
+            None => return false,
+        };
+
+        if accepted.typ != commit.typ
+            || accepted.msg != commit.msg
+            || signer_bitmap(&commit.signers) >= signer_bitmap(&accepted.signers)
+        {
+            return false;
+        }
+
+        if !in_domain(&self.config.domain, &commit.msg)
+            || verify_aggregate(commit, self.threshold()).is_err()
+        {
+            return false;
+        }
+
+        info!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\tCanonicalAggregate: adopting signers {:?} over {:?}",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+            commit.signers,
+            accepted.signers,
+        );
+        self.slot_info.aggregate = Some(commit.clone());
+        true
+    }
+
+    // Reports an aggregate for the current slot which disagrees, in type or message, with the one
+    // already accepted. Only the accepted aggregate is retained.
+    fn check_conflict(&mut self, commit: &Commit) {
+        let accepted = match &self.slot_info.aggregate {
+            Some(accepted) => accepted,
+            None => return,
+        };
+
+        if accepted.typ == commit.typ && accepted.msg == commit.msg {
+            return;
+        }
+
+        if self.verify(commit, None).is_err() {
+            return;
+        }
+
+        warn!(
+            "Slot {}\tBounce Unit {}\tSafetyViolation: {:?} aggregate conflicts with {:?} aggregate",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+            accepted.typ(),
+        );
+        self.emit(Event::SafetyViolation {
+            i: self.slot_info.i,
+            accepted: accepted.clone(),
+            conflicting: commit.clone(),
+        });
+        self.protocol_violation(ProtocolViolation::ConflictingAggregate {
+            i: self.slot_info.i,
+        });
+    }
+
+    // Reports a signer which signed another message than the one of its collected commit of the
+    // same type.
+    fn check_equivocation(&mut self, commit: &Commit) {
+        let equivocates = self
+            .get_commits(commit.typ())
+            .iter()
+            .any(|c| c.public_key == commit.public_key && c.msg != commit.msg);
+        if !equivocates || self.verify(commit, None).is_err() {
+            return;
+        }
+
+        warn!(
+            "Slot {}\tBounce Unit {}\tCommit Type {:?}\t{} signed two different messages",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+            commit.signer_id,
+        );
+        self.protocol_violation(ProtocolViolation::Equivocation {
+            signer_id: commit.signer_id,
+        });
+    }
+
+    // Stops this Bounce unit in strict mode. Otherwise the offending commit is only dropped.
+    fn protocol_violation(&mut self, violation: ProtocolViolation) {
+        if !self.config.strict || self.violation.is_some() {
+            return;
+        }
+
+        error!(
+            "Slot {}\tBounce Unit {}\tProtocolViolation: {:?}, stopping",
+            self.slot_info.i, self.id, violation,
+        );
+        self.slot_info.phase = Phase::Stop;
+        self.noncommit_at = None;
+        self.aggregate_retry = None;
+        self.violation = Some(violation);
+    }
+
+    // Reports a commit whose type is inconsistent with the current phase.
+    fn report_desync(&self, commit: &Commit) {
+        warn!(
+            "Slot {}\tBounce Unit {}\tPhaseDesync: {:?} from {} in phase {:?}",
+            self.slot_info.i,
+            self.id,
+            commit.typ(),
+            commit.signer_id,
+            self.slot_info.phase,
+        );
+        self.emit(Event::PhaseDesync {
+            i: self.slot_info.i,
+            phase: self.slot_info.phase.clone(),
+            typ: commit.typ(),
+            signer_id: commit.signer_id,
+        });
+    }
+
+    async fn aggregate_if_supermajority(&mut self) {
+        if self.slot_info.aggregated || self.slot_info.aborted || self.is_observer() {
+            return;
+        }
+
+        let threshold = self.threshold();
+        if self.slot_info.precommits.len() >= threshold {
+            self.aggregate_and_broadcast(CommitType::Precommit).await;
+        } else if self.slot_info.noncommits.len() >= threshold {
+            self.aggregate_and_broadcast(CommitType::Noncommit).await;
+        }
+    }
+
+    // Signs and broadcasts a noncommit for (j+1, i) in phase 3, unless the phase 3 policy has this
+    // Bounce unit abstain.
+    async fn sign_noncommit(&mut self) {
+        if self.is_observer() || self.slot_info.aborted {
+            return;
+        }
+        // Without phase 3, a slot which doesn't finalize in time is simply left behind.
+        if !self.config.enabled_phases().contains(&Phase::Third) {
+            return;
+        }
+
+        let abstain = match self.config.phase3_policy() {
+            Phase3Policy::AbstainIfPrecommitted => self.slot_info.signed,
+            Phase3Policy::AlwaysNoncommit => self
+                .slot_info
+                .noncommits
+                .iter()
+                .any(|c| c.public_key == self.public_key.as_bytes()),
+        };
+        if abstain {
+            return;
+        }
+
+        let msg = noncommit_message(&self.config.domain, self.slot_info.j, self.slot_info.i);
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: self.slot_info.i,
+            j: self.slot_info.j,
+            msg,
+            public_key: self.public_key.as_bytes().to_vec(),
+            aggregated: false,
+            signer_id: self.id as u32,
+            ..Default::default()
+        };
+        if let Some(noncommit) = self.sign_and_broadcast(noncommit).await {
+            self.slot_info.noncommits.push(noncommit);
+        }
+    }
+
+    // Moves this Bounce unit to the given phase of the slot.
+    pub(crate) async fn enter_phase(&mut self, phase: Phase) {
+        if self.slot_frozen && phase == Phase::First {
+            info!(
+                "Slot {}\tBounce Unit {}\tSlotFrozen: not starting the next slot",
+                self.slot_info.i, self.id,
+            );
+            return;
+        }
+
+        let enabled = self.config.enabled_phases();
+        if phase != Phase::Stop && !enabled.contains(&phase) {
+            return;
+        }
+
+        if !self.slot_info.phase.can_transition_within(&phase, enabled) {
+            // Phases of the slots skipped while frozen are expected.
+            if self.slot_frozen {
+                return;
+            }
+            error!(
+                "Slot {}\tBounce Unit {}\tillegal phase transition from {:?} to {:?}, ignored",
+                self.slot_info.i, self.id, self.slot_info.phase, phase,
+            );
+            return;
+        }
+
+        match phase {
+            Phase::First => {
+                self.noncommit_at = None;
+                self.aggregate_retry = None;
+                self.slot_started = Some(Instant::now());
+                self.slot_info.next();
+                info!(
+                    "Slot {}\tBounce Unit {}\tFirst Phase Starts",
+                    self.slot_info.i, self.id,
+                );
+                self.rotate_epoch_key();
+
+                if !self.quorum_reachable() {
+                    let (reachable, needed) = (self.num_reachable(), self.threshold());
+                    warn!(
+                        "Slot {}\tBounce Unit {}\tQuorumUnreachable: {} Bounce units reachable, {} needed",
+                        self.slot_info.i, self.id, reachable, needed,
+                    );
+                    self.emit(Event::QuorumUnreachable {
+                        i: self.slot_info.i,
+                        reachable,
+                        needed,
+                    });
+                    if self.config.downshift_on_unreachable_quorum {
+                        self.sign_noncommit().await;
+                    }
+                }
+            }
+            Phase::Second => {
+                if !self.quorum_reachable() {
+                    // No aggregate can be produced this slot, so don't wait until phase 3 to sign
+                    // a noncommit.
+                    warn!(
+                        "Slot {}\tBounce Unit {}\tQuorumUnreachable: signing noncommit",
+                        self.slot_info.i, self.id,
+                    );
+                    self.sign_noncommit().await;
+                }
+            }
+            Phase::Third => {
+                let tolerance = self.clock_skew_tolerance();
+                if self.in_startup_grace() {
+                    info!(
+                        "Slot {}\tBounce Unit {}\tStartupGrace: abstaining from noncommit",
+                        self.slot_info.i, self.id,
+                    );
+                } else if tolerance == Duration::from_secs(0) {
+                    self.sign_noncommit().await;
+                } else {
+                    self.noncommit_at = Some(Instant::now() + tolerance);
+                }
+            }
+            Phase::Stop => {}
+        }
+        self.slot_info.phase = phase;
+
+        if self.slot_info.phase == Phase::First && self.is_leader() {
+            self.initiate_precommit().await;
+        }
+    }
+
+    // Whether this Bounce unit initiates the precommit of each slot in place of a ground station.
+    fn is_leader(&self) -> bool {
+        self.config.leader == Some(self.id as u32)
+    }
+
+    // Signs and broadcasts the initial precommit of this slot, which the other Bounce units sign
+    // in turn as if it came from a ground station.
+    async fn initiate_precommit(&mut self) {
+        if self.is_observer() {
+            return;
+        }
+
+        info!(
+            "Slot {}\tBounce Unit {}\tLeader: initiating precommit",
+            self.slot_info.i, self.id,
+        );
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: self.slot_info.i,
+            j: self.slot_info.j,
+            msg: precommit_message(&self.config.domain, self.slot_info.j, self.slot_info.i),
+            ..Default::default()
+        };
+        if let Some(signed) = self.sign_and_broadcast(precommit).await {
+            // Collecting its own precommit can't fail at the start of a slot.
+            let _ = self.push_commit(signed);
+        }
+    }
+
+    // Abandons the aggregation of the current slot, discarding the commits collected so far. This
+    // Bounce unit abstains for the remainder of the slot.
+    fn abort_slot(&mut self) {
+        warn!(
+            "Slot {}\tBounce Unit {}\tSlotAborted: discarding {} precommits and {} noncommits",
+            self.slot_info.i,
+            self.id,
+            self.slot_info.precommits.len(),
+            self.slot_info.noncommits.len(),
+        );
+        self.slot_info.aborted = true;
+        self.slot_info.precommits.clear();
+        self.slot_info.noncommits.clear();
+        self.noncommit_at = None;
+    }
+
+    // The command to act on, unwrapped from its signature. If a controller key is configured,
+    // commands which aren't signed by the controller for this Bounce unit are logged and dropped,
+    // as are replays of commands already acted on.
+    fn authenticate(&mut self, command: Command) -> Option<Command> {
+        if self.config.controller_key.is_empty() {
+            return match command {
+                Command::Signed { command, .. } => self.authenticate(*command),
+                command => Some(command),
+            };
+        }
+
+        let description = format!("{:?}", command);
+        let (command, counter) = match command.authenticated(
+            &self.config.domain,
+            &self.config.controller_key,
+            self.id as u32,
+        ) {
+            Some(authenticated) => authenticated,
+            None => {
+                warn!(
+                    "Slot {}\tBounce Unit {}\tUnauthenticatedCommand: {}, dropped",
+                    self.slot_info.i, self.id, description,
+                );
+                return None;
+            }
+        };
+
+        if matches!(self.last_command_counter, Some(last) if counter <= last) {
+            warn!(
+                "Slot {}\tBounce Unit {}\tReplayedCommand: {}, dropped",
+                self.slot_info.i, self.id, description,
+            );
+            return None;
+        }
+        self.last_command_counter = Some(counter);
+        Some(command)
+    }
+
+    // When the phase 3 noncommit delayed by the clock skew tolerance is due.
+    pub(crate) fn noncommit_at(&self) -> Option<Instant> {
+        self.noncommit_at
+    }
+
+    // Signs the phase 3 noncommit delayed by the clock skew tolerance, once it's due.
+    pub(crate) async fn sign_noncommit_if_due(&mut self) {
+        match self.noncommit_at {
+            Some(at) if at <= Instant::now() => {
+                self.noncommit_at = None;
+                self.sign_noncommit().await;
+            }
+            _ => {}
+        }
+    }
+
+    pub async fn run(&mut self) -> RunOutcome {
+        self.run_until_cancelled(CancellationToken::new()).await
+    }
+
+    /// Same as `run`, but also shuts down when the token is cancelled. Sharing one token between
+    /// Bounce units lets a parent task stop all of them at once.
+    pub async fn run_until_cancelled(&mut self, token: CancellationToken) -> RunOutcome {
+        if self.num_cubesats() == 0 {
+            error!("Bounce Unit {}\tnum_cubesats must be positive", self.id);
+            return RunOutcome::ConfigInvalid;
+        }
+
+        if self.terminating {
+            return RunOutcome::Terminated;
+        }
+
+        let heartbeat_interval = self.heartbeat_interval();
+        // The period is only used if heartbeats are enabled, but must be positive.
+        let period = heartbeat_interval.unwrap_or(Duration::from_secs(1));
+        let mut heartbeat = time::interval_at(Instant::now() + period, period);
+        heartbeat.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        loop {
+            if self.result_closed {
+                return RunOutcome::FatalErrors;
+            }
+            if let Some(violation) = self.violation.take() {
+                return RunOutcome::ProtocolViolation(violation);
+            }
+
+            tokio::select! {
+                _ = token.cancelled() => {
+                    info!("Slot {}\tBounce Unit {}\tCancelled", self.slot_info.i, self.id);
+                    return RunOutcome::Cancelled;
+                }
+                command = self.command_rx.recv() => {
+                    let command = match command {
+                        Some(command) => command,
+                        None => {
+                            warn!(
+                                "Slot {}\tBounce Unit {}\tCommandChannelClosed",
+                                self.slot_info.i,
+                                self.id,
+                            );
+                            return RunOutcome::CommandChannelClosed;
+                        }
+                    };
+                    let command = match self.authenticate(command) {
+                        Some(command) => command,
+                        None => continue,
+                    };
+                    match command {
+                        Command::Terminate => {
+                            info!(
+                                "Slot {}\tBounce Unit {}\tTerminated",
+                                self.slot_info.i,
+                                self.id,
+                            );
+                            self.terminating = true;
+                            return RunOutcome::Terminated;
+                        }
+                        Command::Pause => {
+                            info!("Slot {}\tBounce Unit {}\tPaused", self.slot_info.i, self.id);
+                            self.paused = true;
+                        }
+                        Command::Resume => {
+                            info!("Slot {}\tBounce Unit {}\tResumed", self.slot_info.i, self.id);
+                            self.paused = false;
+                        }
+                        Command::FreezeSlot => {
+                            info!("Slot {}\tBounce Unit {}\tSlot frozen", self.slot_info.i, self.id);
+                            self.slot_frozen = true;
+                        }
+                        Command::UnfreezeSlot => {
+                            info!("Slot {}\tBounce Unit {}\tSlot unfrozen", self.slot_info.i, self.id);
+                            self.slot_frozen = false;
+                        }
+                        Command::AbortSlot => self.abort_slot(),
+                        Command::SlotStatus { i, reply } => {
+                            // The caller may have stopped waiting.
+                            let _ = reply.send(self.slot_info.history.get(i).cloned());
+                        }
+                        Command::GetPhase(reply) => {
+                            let _ = reply.send(self.slot_info.phase.clone());
+                        }
+                        // Unwrapped by `authenticate`.
+                        Command::Signed { .. } => {}
+                    }
+                }
+                Ok(phase) = self.timer_rx.recv(), if !self.paused => {
+                    self.enter_phase(phase).await;
+                }
+                _ = heartbeat.tick(), if heartbeat_interval.is_some() && !self.paused => {
+                    self.send_heartbeat().await;
+                }
+                _ = time::sleep_until(self.noncommit_at.unwrap_or_else(Instant::now)), if self.noncommit_at.is_some() && !self.paused => {
+                    self.sign_noncommit_if_due().await;
+                }
+                _ = time::sleep_until(self.aggregate_retry.map_or_else(Instant::now, |(at, _)| at)), if self.aggregate_retry.is_some() && !self.paused => {
+                    self.retry_aggregate_if_due().await;
+                }
+                Some((commit, valid)) = self.verified_rx.recv() => {
+                    if self.paused {
+                        self.metrics.inc_commits_dropped();
+                    } else {
+                        let start = std::time::Instant::now();
+                        self.process_verified(commit, Some(valid)).await;
+                        self.slot_info.processing += start.elapsed();
+                    }
+                }
+                commit = self.request_rx.recv() => {
+                    match commit {
+                        Some(_) if self.paused => self.metrics.inc_commits_dropped(),
+                        Some(commit) if self.over_budget() => self.defer(commit),
+                        Some(commit) => self.handle_request(commit).await,
+                        None => {
+                            info!(
+                                "Slot {}\tBounce Unit {}\tRequest channel closed",
+                                self.slot_info.i,
+                                self.id,
+                            );
+                            return RunOutcome::InputClosed;
+                        }
+                    }
+                }
+                // Yielding first lets any other branch which is ready win, so commits set aside
+                // are only processed once nothing else is pending.
+                _ = tokio::task::yield_now(), if !self.deferred.is_empty() && !self.paused => {
+                    if let Some(commit) = self.deferred.pop_front() {
+                        self.handle_request(commit).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{supermajority, CommandError, PhaseSet, PrivateKey, SlotDriver, TimerDriver};
+    use bls_signatures_rs::MultiSignature;
+    use rand::{thread_rng, Rng};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::oneshot;
+
+    fn signed_commit(typ: CommitType, i: u32, j: u32, msg: &[u8], signer_id: u32) -> Commit {
+        let mut rng = thread_rng();
+        let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+
+        Commit {
+            typ: typ.into(),
+            i,
+            j,
+            msg: msg.to_vec(),
+            public_key: Bn256.derive_public_key(&private_key).unwrap(),
+            signature: Bn256.sign(&private_key, msg).unwrap(),
+            aggregated: false,
+            signer_id,
+            ..Default::default()
+        }
+    }
+
+    // Feeds commits to the Bounce unit until it aggregates, and returns the aggregate after
+    // checking that it verifies.
+    async fn drive_to_aggregate(c: &mut Cubesat, commits: Vec<Commit>) -> Commit {
+        for commit in commits {
+            if c.process(commit).await.aggregated {
+                let aggregate = c.slot_info.aggregate.clone().unwrap();
+                assert_eq!(crate::verify_aggregate(&aggregate, c.threshold()), Ok(()));
+                return aggregate;
+            }
+        }
+        panic!("Bounce Unit {} didn't aggregate", c.id);
+    }
+
+    // An aggregate of fresh signers with ids 1 to n.
+    fn aggregate_commit(typ: CommitType, i: u32, j: u32, msg: &[u8], n: u32) -> Commit {
+        let commits: Vec<Commit> = (1..=n)
+            .map(|signer_id| signed_commit(typ, i, j, msg, signer_id))
+            .collect();
+        let (signature, public_key) = Cubesat::aggregate(&commits).unwrap();
+
+        Commit {
+            typ: typ.into(),
+            i,
+            j,
+            msg: msg.to_vec(),
+            public_key,
+            signature,
+            aggregated: true,
+            signer_id: 1,
+            signer_count: n,
+            signers: (1..=n).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn cubesat_sign_aggregate() {
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::First;
+
+        tokio::spawn(async move {
+            c.run().await;
+        });
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let ground_station_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let ground_station_public_key = Bn256
+            .derive_public_key(&ground_station_private_key)
+            .unwrap();
+        let signature = Bn256.sign(&ground_station_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: ground_station_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        tokio::spawn(async move {
+            request_tx
+                .send(precommit)
+                .await
+                .expect("failed to send precommit");
+        });
+
+        let result_opt = result_rx.recv().await;
+        assert!(result_opt.is_some());
+        let commit = result_opt.unwrap();
+
+        assert_eq!(commit.typ(), CommitType::Precommit);
+        assert_eq!(commit.i, 0);
+        assert_eq!(commit.msg, msg);
+        assert!(!commit.aggregated);
+
+        let result_opt = result_rx.recv().await;
+        assert!(result_opt.is_some());
+        let commit = result_opt.unwrap();
+
+        assert_eq!(commit.typ(), CommitType::Precommit);
+        assert_eq!(commit.i, 0);
+        assert_eq!(commit.msg, msg);
+        assert!(commit.aggregated);
+
+        Bn256
+            .verify(&commit.signature, &msg, &commit.public_key)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn run_terminated() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        let handle = tokio::spawn(async move { c.run().await });
+
+        command_tx.send(Command::Terminate).await.unwrap();
+        assert_eq!(handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn run_authenticated_commands() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(3);
+
+        let controller = LocalSigner::random();
+        let config = BounceConfig {
+            num_cubesats: 1,
+            domain: b"constellation-a".to_vec(),
+            controller_key: controller.public_key().into_bytes(),
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        let handle = tokio::spawn(async move { c.run().await });
+
+        // Neither an unsigned command, nor one signed by another key, nor one signed for another
+        // Bounce unit is acted on.
+        command_tx.send(Command::Terminate).await.unwrap();
+        let forged = Command::Terminate
+            .signed(b"constellation-a", 0, 0, &LocalSigner::random())
+            .unwrap();
+        command_tx.send(forged).await.unwrap();
+        let redirected = Command::Terminate
+            .signed(b"constellation-a", 1, 0, &controller)
+            .unwrap();
+        command_tx.send(redirected).await.unwrap();
+        let (reply, status) = oneshot::channel();
+        command_tx
+            .send(Command::SlotStatus { i: 0, reply })
+            .await
+            .unwrap();
+        // The unsigned query is dropped along with its reply.
+        assert!(status.await.is_err());
+        assert!(!handle.is_finished());
+
+        let terminate = Command::Terminate
+            .signed(b"constellation-a", 0, 1, &controller)
+            .unwrap();
+        command_tx.send(terminate).await.unwrap();
+        assert_eq!(handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn replayed_command_dropped() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(3);
+
+        let controller = LocalSigner::random();
+        let config = BounceConfig {
+            num_cubesats: 1,
+            domain: b"constellation-a".to_vec(),
+            controller_key: controller.public_key().into_bytes(),
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        let handle = tokio::spawn(async move { c.run().await });
+
+        let (reply, status) = oneshot::channel();
+        let command = Command::SlotStatus { i: 0, reply }
+            .signed(b"constellation-a", 0, 5, &controller)
+            .unwrap();
+        let signature = match &command {
+            Command::Signed { signature, .. } => signature.clone(),
+            _ => unreachable!(),
+        };
+        command_tx.send(command).await.unwrap();
+        assert!(status.await.is_ok());
+
+        // The same signed command sent again is dropped along with its reply.
+        let (reply, status) = oneshot::channel();
+        command_tx
+            .send(Command::Signed {
+                command: Box::new(Command::SlotStatus { i: 0, reply }),
+                target: 0,
+                counter: 5,
+                signature,
+            })
+            .await
+            .unwrap();
+        assert!(status.await.is_err());
+
+        // So is a command signed with an older counter.
+        let (reply, status) = oneshot::channel();
+        let stale = Command::SlotStatus { i: 0, reply }
+            .signed(b"constellation-a", 0, 4, &controller)
+            .unwrap();
+        command_tx.send(stale).await.unwrap();
+        assert!(status.await.is_err());
+
+        let terminate = Command::Terminate
+            .signed(b"constellation-a", 0, 6, &controller)
+            .unwrap();
+        command_tx.send(terminate).await.unwrap();
+        assert_eq!(handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn handle_authenticated_commands() {
+        let (timer_tx, _timer_rx) = broadcast::channel(15);
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let controller = Arc::new(LocalSigner::random());
+        let config = BounceConfig {
+            num_cubesats: 1,
+            domain: b"constellation-a".to_vec(),
+            controller_key: controller.public_key().into_bytes(),
+            ..Default::default()
+        };
+        let (handle, join_handle) = Cubesat::spawn(0, config, result_tx, timer_tx.subscribe());
+
+        // The unsigned command would be dropped by the Bounce unit, so the handle refuses it.
+        match handle.pause().await {
+            Err(CommandError::Unsigned(Command::Pause)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        // Only a Bounce unit which is gone makes terminating a no-op.
+        assert!(matches!(
+            handle.terminate().await,
+            Err(CommandError::Unsigned(Command::Terminate))
+        ));
+
+        let handle = handle.with_controller(b"constellation-a".to_vec(), controller, 1);
+        let (reply, status) = oneshot::channel();
+        handle
+            .command(Command::SlotStatus { i: 0, reply })
+            .await
+            .unwrap();
+        assert!(status.await.is_ok());
+
+        // Clones share the counter, so their commands aren't dropped as replays.
+        handle.clone().terminate().await.unwrap();
+        assert_eq!(join_handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn freeze_slot() {
+        time::pause();
+
+        let (result_tx, _result_rx) = mpsc::channel(15);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, timer_rx, command_rx);
+
+        // Runs the Bounce unit through the given phases, one second apart, after a command.
+        async fn drive(
+            mut c: Cubesat,
+            command_tx: &mpsc::Sender<Command>,
+            timer_tx: &broadcast::Sender<Phase>,
+            command: Command,
+            phases: &[Phase],
+        ) -> Cubesat {
+            let token = CancellationToken::new();
+            let child = token.clone();
+            let handle = tokio::spawn(async move {
+                c.run_until_cancelled(child).await;
+                c
+            });
+            command_tx.send(command).await.unwrap();
+            tokio::task::yield_now().await;
+            for phase in phases {
+                time::advance(Duration::from_secs(1)).await;
+                timer_tx.send(phase.clone()).unwrap();
+                tokio::task::yield_now().await;
+            }
+            token.cancel();
+            handle.await.unwrap()
+        }
+
+        let slot = [Phase::First, Phase::Second, Phase::Third];
+        for _ in 0..3 {
+            c.enter_phase(Phase::First).await;
+        }
+        assert_eq!(c.slot(), 3);
+
+        // The frozen slot still goes through its phases, but the next slots don't start.
+        let phases: Vec<Phase> = [Phase::Second, Phase::Third]
+            .iter()
+            .chain(slot.iter().cycle().take(6))
+            .cloned()
+            .collect();
+        let c = drive(c, &command_tx, &timer_tx, Command::FreezeSlot, &phases).await;
+        assert_eq!(c.slot(), 3);
+        assert_eq!(c.phase(), &Phase::Third);
+
+        let c = drive(c, &command_tx, &timer_tx, Command::UnfreezeSlot, &slot).await;
+        assert_eq!(c.slot(), 4);
+        assert_eq!(c.phase(), &Phase::Third);
+    }
+
+    #[tokio::test]
+    async fn run_command_channel_closed() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        drop(command_tx);
+        assert_eq!(c.run().await, RunOutcome::CommandChannelClosed);
+    }
+
+    #[tokio::test]
+    async fn get_phase() {
+        time::pause();
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let config = BounceConfig {
+            num_cubesats: 1,
+            slot_duration: 10,
+            phase1_duration: 4,
+            phase2_duration: 3,
+            ..Default::default()
+        };
+        let mut driver = TimerDriver::new(config.clone());
+        let (handle, task) = Cubesat::spawn(0, config, result_tx, driver.subscribe());
+        driver.start();
+        time::sleep(Duration::from_millis(100)).await;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        handle.command(Command::GetPhase(reply_tx)).await.unwrap();
+        assert_eq!(reply_rx.await.unwrap(), Phase::First);
+
+        handle.terminate().await.unwrap();
+        assert_eq!(task.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn run_from_start_slot() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new_at_slot(0, 3, result_tx, request_rx, timer_rx, command_rx, 100);
+        let handle = tokio::spawn(async move {
+            c.run().await;
+            c
+        });
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        command_tx.send(Command::Terminate).await.unwrap();
+
+        let c = handle.await.unwrap();
+        assert_eq!(c.slot_info.i, 100);
+        assert_eq!(c.slot_info.phase, Phase::First);
+    }
+
+    #[tokio::test]
+    async fn run_terminated_twice() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(2);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        let handle = tokio::spawn(async move { (c.run().await, c) });
+
+        command_tx.send(Command::Terminate).await.unwrap();
+        command_tx.send(Command::Terminate).await.unwrap();
+        let (outcome, mut c) = handle.await.unwrap();
+        assert_eq!(outcome, RunOutcome::Terminated);
+
+        // Running again neither processes commits nor consumes the second Terminate.
+        let msg = crate::precommit_message(&[], 0, 1);
+        request_tx
+            .send(signed_commit(CommitType::Precommit, 1, 0, &msg, 1))
+            .await
+            .unwrap();
+        assert_eq!(c.run().await, RunOutcome::Terminated);
+        assert_eq!(c.metrics().commits_received, 0);
+
+        // Terminating through a handle once the Bounce unit is gone isn't an error.
+        let (timer_tx, _timer_rx) = broadcast::channel(15);
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let config = BounceConfig {
+            num_cubesats: 1,
+            ..Default::default()
+        };
+        let (handle, join_handle) = Cubesat::spawn(0, config, result_tx, timer_tx.subscribe());
+        handle.terminate().await.unwrap();
+        handle.terminate().await.unwrap();
+        assert_eq!(join_handle.await.unwrap(), RunOutcome::Terminated);
+        handle.terminate().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn abort_slot_prevents_aggregate() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let task = tokio::spawn(async move {
+            c.run().await;
+            c
+        });
+
+        request_tx
+            .send(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert!(!result_rx.try_recv().unwrap().aggregated);
+
+        command_tx.send(Command::AbortSlot).await.unwrap();
+        tokio::task::yield_now().await;
+
+        // Enough precommits for an aggregate along with the one signed by the Bounce unit.
+        for signer_id in 2..=3 {
+            request_tx
+                .send(signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+                .await
+                .unwrap();
+            tokio::task::yield_now().await;
+        }
+
+        drop(request_tx);
+        let c = task.await.unwrap();
+        assert!(result_rx.try_recv().is_err());
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.aborted);
+        assert!(c.slot_info.precommits.is_empty());
+        assert_eq!(c.slot_info.i, i);
+    }
+
+    #[tokio::test]
+    async fn illegal_phase_transition_ignored() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::Second).await;
+        assert_eq!(c.slot_info.phase, Phase::Stop);
+
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::Second).await;
+        c.enter_phase(Phase::Third).await;
+        c.enter_phase(Phase::Second).await;
+        assert_eq!(c.slot_info.phase, Phase::Third);
+    }
+
+    #[tokio::test]
+    async fn strict_mode_stops_on_invalid_signature() {
+        for strict in [false, true].iter() {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+            let config = BounceConfig {
+                num_cubesats: 3,
+                strict: *strict,
+                ..Default::default()
+            };
+
+            let mut c =
+                Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+            c.enter_phase(Phase::First).await;
+
+            let (i, j) = (c.slot_info.i, c.slot_info.j);
+            let msg = crate::precommit_message(&[], j, i);
+            let mut precommit = signed_commit(CommitType::Precommit, i, j, &msg, 1);
+            precommit.signature = signed_commit(CommitType::Precommit, i, j, b"other", 1).signature;
+
+            let outcome = c.process(precommit).await;
+            assert_eq!(outcome.dropped, Some(DropReason::InvalidSignature));
+
+            if *strict {
+                assert_eq!(c.slot_info.phase, Phase::Stop);
+                assert_eq!(
+                    c.run().await,
+                    RunOutcome::ProtocolViolation(ProtocolViolation::InvalidSignature {
+                        signer_id: 1
+                    })
+                );
+            } else {
+                assert_eq!(c.slot_info.phase, Phase::First);
+                drop(request_tx);
+                assert_eq!(c.run().await, RunOutcome::InputClosed);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn slot_mismatch_dropped() {
+        for strict in [false, true].iter() {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (_request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+            let config = BounceConfig {
+                num_cubesats: 3,
+                strict: *strict,
+                ..Default::default()
+            };
+
+            let mut c =
+                Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+            c.enter_phase(Phase::First).await;
+
+            // A valid precommit, but for the next slot.
+            let (i, j) = (c.slot_info.i + 1, c.slot_info.j);
+            let msg = crate::precommit_message(&[], j, i);
+            let outcome = c
+                .process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+                .await;
+            assert!(!outcome.signed);
+            assert!(c.slot_info.precommits.is_empty());
+            assert_eq!(c.metrics().commits_dropped, 1);
+
+            if *strict {
+                assert_eq!(outcome.dropped, Some(DropReason::Stopped));
+                assert_eq!(c.slot_info.phase, Phase::Stop);
+            } else {
+                assert_eq!(outcome.dropped, Some(DropReason::SlotMismatch));
+                assert_eq!(c.slot_info.phase, Phase::First);
+            }
+        }
+    }
+
+    #[cfg(feature = "debug-invariants")]
+    #[tokio::test]
+    async fn no_signing_after_aggregation() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::Second).await;
+        c.slot_info.aggregated = true;
+
+        // Nothing else stops this Bounce unit, which hasn't signed yet, from signing a noncommit
+        // in phase 3.
+        c.enter_phase(Phase::Third).await;
+        assert!(!c.slot_info.signed);
+        assert!(result_rx.try_recv().is_err());
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = signed_commit(CommitType::Precommit, i, j, &[1], 1);
+        assert_eq!(c.sign_and_broadcast(precommit).await, None);
+        assert!(!c.slot_info.signed);
+        assert_eq!(c.metrics().signatures, 0);
+    }
+
+    #[tokio::test]
+    async fn slow_verification_does_not_delay_phases() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            verify_threads: 1,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        c.set_signature_verifier(Arc::new(|commit: &Commit| {
+            std::thread::sleep(Duration::from_millis(500));
+            verify_signature(commit)
+        }));
+        let task = tokio::spawn(async move {
+            c.run().await;
+            c
+        });
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        let msg = crate::precommit_message(&[], 0, 1);
+        request_tx
+            .send(signed_commit(CommitType::Precommit, 1, 0, &msg, 1))
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+
+        // The next slot starts on schedule while the precommit is still being verified.
+        let start = std::time::Instant::now();
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        command_tx
+            .send(Command::SlotStatus {
+                i: 1,
+                reply: reply_tx,
+            })
+            .await
+            .unwrap();
+        let record = reply_rx.await.unwrap().unwrap();
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(record.precommits, 0);
+
+        // Once verified, the precommit is processed against the state of slot 2.
+        time::sleep(Duration::from_millis(600)).await;
+        command_tx.send(Command::Terminate).await.unwrap();
+        let c = task.await.unwrap();
+        assert_eq!(c.slot_info.i, 2);
+        assert_eq!(c.metrics().commits_received, 1);
+        assert_eq!(c.history().get(1).unwrap().precommits, 0);
+    }
+
+    // The verifier blocks the thread running the Bounce unit, so the test runs on another one.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn slot_cpu_budget() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            slot_cpu_budget_ms: 250,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        c.set_signature_verifier(Arc::new(|commit: &Commit| {
+            std::thread::sleep(Duration::from_millis(100));
+            verify_signature(commit)
+        }));
+        let metrics = c.metrics_handle();
+        let task = tokio::spawn(async move {
+            c.run().await;
+            c
+        });
+
+        timer_tx.send(Phase::First).unwrap();
+        time::sleep(Duration::from_millis(20)).await;
+
+        // Noncommits are verified, then dropped in phase 1, so each costs the full verification.
+        let msg = crate::noncommit_message(&[], 0, 1);
+        for signer_id in 1..=10 {
+            request_tx
+                .send(signed_commit(CommitType::Noncommit, 1, 0, &msg, signer_id))
+                .await
+                .unwrap();
+        }
+
+        // Three commits exhaust the budget, and the rest is set aside.
+        time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(metrics.snapshot().commits_deferred, 7);
+
+        // The next slot starts after at most one more commit, rather than once all the set aside
+        // ones are verified.
+        let start = std::time::Instant::now();
+        timer_tx.send(Phase::First).unwrap();
+        loop {
+            // Slot 1 is only recorded once slot 2 starts.
+            let (reply_tx, reply_rx) = oneshot::channel();
+            command_tx
+                .send(Command::SlotStatus {
+                    i: 1,
+                    reply: reply_tx,
+                })
+                .await
+                .unwrap();
+            if reply_rx.await.unwrap().is_some() {
+                break;
+            }
+        }
+        assert!(start.elapsed() < Duration::from_millis(300));
+
+        // Commits set aside are processed once the Bounce unit is idle.
+        time::sleep(Duration::from_millis(1000)).await;
+        command_tx.send(Command::Terminate).await.unwrap();
+        task.await.unwrap();
+        assert_eq!(metrics.snapshot().commits_received, 10);
+    }
+
+    #[tokio::test]
+    async fn redelivered_commit_skips_verification() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        let verifications = Arc::new(AtomicU32::new(0));
+        let counter = verifications.clone();
+        c.set_signature_verifier(Arc::new(move |commit: &Commit| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            verify_signature(commit)
+        }));
+        c.enter_phase(Phase::First).await;
+
+        // A noncommit is verified, then dropped as unexpected in phase 1, so its redelivery isn't
+        // caught as a duplicate.
+        let msg = crate::noncommit_message(&[], 0, 1);
+        let noncommit = signed_commit(CommitType::Noncommit, 1, 0, &msg, 1);
+        for _ in 0..2 {
+            let outcome = c.process(noncommit.clone()).await;
+            assert_eq!(outcome.dropped, Some(DropReason::WrongPhase));
+        }
+        assert_eq!(verifications.load(Ordering::SeqCst), 1);
+
+        // The cache only lasts for the slot.
+        c.enter_phase(Phase::First).await;
+        assert!(c.slot_info.signature_cache.is_empty());
+        c.process(noncommit).await;
+        assert_eq!(verifications.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn compressed_aggregate() {
+        for uncompressed_points in [false, true].iter() {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (_request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+
+            let config = BounceConfig {
+                num_cubesats: 3,
+                uncompressed_points: *uncompressed_points,
+                ..Default::default()
+            };
+            let mut c =
+                Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+            c.enter_phase(Phase::First).await;
+
+            let (i, j) = (c.slot_info.i, c.slot_info.j);
+            let msg = crate::precommit_message(&[], j, i);
+            c.process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+                .await;
+            c.process(signed_commit(CommitType::Precommit, i, j, &msg, 2))
+                .await;
+            let aggregate = c.slot_info.aggregate.clone().unwrap();
+
+            let (signature_length, public_key_length) = if *uncompressed_points {
+                (64, 128)
+            } else {
+                (33, 65)
+            };
+            assert_eq!(aggregate.signature.len(), signature_length);
+            assert_eq!(aggregate.public_key.len(), public_key_length);
+            assert_eq!(verify_aggregate(&aggregate, 2), Ok(()));
+
+            // Either form verifies once converted to the other.
+            let converted = if *uncompressed_points {
+                crate::with_compressed_points(&aggregate)
+                    .unwrap()
+                    .into_owned()
+            } else {
+                with_uncompressed_points(&aggregate).unwrap()
+            };
+            assert_eq!(verify_aggregate(&converted, 2), Ok(()));
+            assert!(verify_signature(&converted));
+        }
+    }
+
+    #[tokio::test]
+    async fn quorum_reached() {
+        time::pause();
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.enter_phase(Phase::First).await;
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        // The first precommit is replaced by the one this Bounce unit signs.
+        time::advance(Duration::from_millis(300)).await;
+        for signer_id in 1..=2 {
+            c.process(signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+                .await;
+        }
+        assert_eq!(c.collected_signers(), (2, 0));
+        assert!(events_rx.try_recv().is_err());
+
+        time::advance(Duration::from_millis(450)).await;
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &msg, 3))
+            .await;
+        assert!(outcome.aggregated);
+        assert_eq!(
+            events_rx.try_recv(),
+            Ok(Event::QuorumReached {
+                i,
+                phase: Phase::First,
+                elapsed_since_slot_start: Duration::from_millis(750),
+            })
+        );
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn reset() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        let public_key = c.public_key().clone();
+        for _ in 0..2 {
+            c.enter_phase(Phase::First).await;
+            let (i, j) = (c.slot_info.i, c.slot_info.j);
+            let msg = crate::precommit_message(&[], j, i);
+            let commits = (1..=2)
+                .map(|signer_id| signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+                .collect();
+            drive_to_aggregate(&mut c, commits).await;
+            assert_eq!(c.slot(), 1);
+
+            c.reset();
+            assert_eq!(c.slot(), 0);
+            assert_eq!(c.phase(), &Phase::Stop);
+            assert!(!c.slot_info.signed);
+            assert!(!c.slot_info.aggregated);
+            assert!(c.slot_info.aggregate.is_none());
+            assert!(c.slot_info.precommits.is_empty());
+            assert!(c.slot_info.noncommits.is_empty());
+            assert!(c.history().is_empty());
+            assert!(c.learned_keys().is_empty());
+            assert_eq!(c.public_key(), &public_key);
+        }
+    }
+
+    #[tokio::test]
+    async fn on_finalize() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        let finalized = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook_finalized = finalized.clone();
+        c.set_on_finalize(Box::new(move |aggregate: &Commit| {
+            hook_finalized.lock().unwrap().push(aggregate.clone());
+        }));
+
+        // Slot 1 finalizes with the aggregate of this Bounce unit, which isn't reported again
+        // when it comes back from a peer.
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let commits = (1..=3)
+            .map(|signer_id| signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+            .collect();
+        let local = drive_to_aggregate(&mut c, commits).await;
+        c.process(local.clone()).await;
+
+        // Slot 2 finalizes with the aggregate of a peer.
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let peer = aggregate_commit(CommitType::Precommit, i, j, &msg, 2);
+        assert!(c.process(peer.clone()).await.aggregated);
+        c.process(peer.clone()).await;
+
+        assert_eq!(*finalized.lock().unwrap(), vec![local, peer]);
+    }
+
+    // Writer appending to a buffer which the test keeps a handle on.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn csv_sink() {
+        time::pause();
+
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        let buffer = SharedBuffer::default();
+        c.csv_sink(buffer.clone()).unwrap();
+
+        // Slot 1 finalizes with a precommit aggregate after 1.5s, slot 2 with a noncommit one
+        // after 4s.
+        c.enter_phase(Phase::First).await;
+        time::advance(Duration::from_millis(1_500)).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let precommit = aggregate_commit(CommitType::Precommit, i, i, &msg, 2);
+        assert!(c.process(precommit).await.aggregated);
+
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::Second).await;
+        c.enter_phase(Phase::Third).await;
+        time::advance(Duration::from_millis(2_500)).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = noncommit_message(&[], j, i);
+        let noncommit = aggregate_commit(CommitType::Noncommit, i, j, &msg, 3);
+        assert!(c.process(noncommit).await.aggregated);
+
+        let csv = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            csv,
+            "i,j,commit_type,signer_count,elapsed_ms\n\
+             1,1,precommit,2,1500\n\
+             2,1,noncommit,3,4000\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn commit_gap() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.enter_phase(Phase::First).await;
+        c.slot_info.j = 2;
+
+        let i = c.slot_info.i;
+        let msg = crate::precommit_message(&[], 10, i);
+        let outcome = c
+            .process(aggregate_commit(CommitType::Precommit, i, 10, &msg, 3))
+            .await;
+        assert!(outcome.dropped.is_none());
+        assert_eq!(c.slot_info.j, 10);
+        assert_eq!(
+            events_rx.try_recv(),
+            Ok(Event::CommitGap { from: 2, to: 10 })
+        );
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn self_aggregate_invalid() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.enter_phase(Phase::First).await;
+
+        // Bypass the signature check of `collect`, so that a collected precommit carries the
+        // same message as the others but a signature over another one.
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        c.slot_info
+            .precommits
+            .push(signed_commit(CommitType::Precommit, i, j, &msg, 1));
+        c.slot_info.precommits.push(Commit {
+            msg: msg.clone(),
+            ..signed_commit(CommitType::Precommit, i, j, b"another message", 2)
+        });
+        c.aggregate_if_supermajority().await;
+
+        assert!(!c.slot_info.aggregated);
+        assert!(result_rx.try_recv().is_err());
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            Event::SelfAggregateInvalid {
+                i,
+                typ: CommitType::Precommit,
+                error: crate::VerifyError::InvalidSignature,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn precommit_justification() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::First).await;
+        c.slot_info.j = 1;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+
+        let encode = |commit: &Commit| {
+            let mut buf = Vec::new();
+            commit.encode(&mut buf).unwrap();
+            buf
+        };
+        let justified_precommit = |justification: Vec<u8>, signer_id| {
+            let msg =
+                crate::with_justification(crate::precommit_message(&[], j, i), &justification);
+            let mut precommit = signed_commit(CommitType::Precommit, i, j, &msg, signer_id);
+            precommit.justification = justification;
+            precommit
+        };
+
+        // The aggregate which committed slot 1.
+        let aggregate = aggregate_commit(
+            CommitType::Precommit,
+            1,
+            1,
+            &crate::precommit_message(&[], 0, 1),
+            3,
+        );
+        let outcome = c.process(justified_precommit(encode(&aggregate), 1)).await;
+        assert_eq!(outcome.dropped, None);
+        assert!(outcome.signed);
+
+        // An aggregate of too few signers doesn't justify anything.
+        let mut bogus = aggregate;
+        bogus.signer_count = 1;
+        bogus.signers.truncate(1);
+        let outcome = c.process(justified_precommit(encode(&bogus), 2)).await;
+        assert_eq!(outcome.dropped, Some(DropReason::InvalidJustification));
+
+        let outcome = c.process(justified_precommit(vec![1, 2, 3], 3)).await;
+        assert_eq!(outcome.dropped, Some(DropReason::InvalidJustification));
+        assert_eq!(c.slot_info.precommits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn aggregate_preview() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        assert_eq!(c.current_aggregate_preview(), None);
+
+        // The first precommit received is collected through the one signed in response, so this
+        // collects 2 of the 3 needed.
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        c.process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await;
+        let signed = result_rx.try_recv().unwrap();
+        let precommit = signed_commit(CommitType::Precommit, i, j, &msg, 2);
+        c.process(precommit.clone()).await;
+
+        let (signature, public_key, signers) = c.current_aggregate_preview().unwrap();
+        assert_eq!(signers, 2);
+        assert!(!c.slot_info.aggregated);
+        assert!(result_rx.try_recv().is_err());
+
+        let expected_public_key = Bn256
+            .aggregate_public_keys(&[&signed.public_key, &precommit.public_key])
+            .unwrap();
+        assert_eq!(public_key, expected_public_key);
+        assert!(Bn256.verify(&signature, &msg, &public_key).is_ok());
+    }
+
+    #[tokio::test]
+    async fn collected_signers() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 7, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        assert_eq!(c.collected_signers(), (0, 0));
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        for signer_id in 1..=2 {
+            let precommit = signed_commit(CommitType::Precommit, i, j, &msg, signer_id);
+            assert_eq!(c.process(precommit).await.dropped, None);
+        }
+
+        // The first precommit is collected through the one signed in response to it.
+        assert_eq!(c.collected_signers(), (2, 0));
+    }
+
+    #[tokio::test]
+    async fn memory_footprint() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 7, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        assert_eq!(c.memory_footprint(), 0);
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        // The first precommit is collected through the one signed in response to it.
+        let precommit = signed_commit(CommitType::Precommit, i, j, &msg, 1);
+        let per_commit = precommit.signature.len() + precommit.public_key.len() + msg.len();
+        c.process(precommit).await;
+        assert_eq!(c.memory_footprint(), per_commit);
+
+        for signer_id in 2..=4 {
+            let precommit = signed_commit(CommitType::Precommit, i, j, &msg, signer_id);
+            assert_eq!(c.process(precommit).await.dropped, None);
+            assert_eq!(c.memory_footprint(), signer_id as usize * per_commit);
+        }
+
+        c.enter_phase(Phase::First).await;
+        assert_eq!(c.memory_footprint(), 0);
+    }
+
+    #[tokio::test]
+    async fn slot_status_command() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        assert_eq!(i, 2);
+        let aggregate = aggregate_commit(
+            CommitType::Noncommit,
+            i,
+            j,
+            &noncommit_message(&[], j, i),
+            2,
+        );
+        c.slot_info.phase = Phase::Third;
+        assert!(c.process(aggregate).await.aggregated);
+        let handle = tokio::spawn(async move { c.run().await });
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        command_tx
+            .send(Command::SlotStatus { i, reply: reply_tx })
+            .await
+            .unwrap();
+        let record = reply_rx.await.unwrap().unwrap();
+        assert!(record.finalized);
+        assert_eq!(record.outcome, Some(CommitType::Noncommit));
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        command_tx
+            .send(Command::SlotStatus {
+                i: 100,
+                reply: reply_tx,
+            })
+            .await
+            .unwrap();
+        assert_eq!(reply_rx.await.unwrap(), None);
+
+        command_tx.send(Command::Terminate).await.unwrap();
+        assert_eq!(handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn run_cancelled() {
+        let token = CancellationToken::new();
+        let mut handles = Vec::new();
+
+        for id in 0..2 {
+            let (result_tx, _result_rx) = mpsc::channel(1);
+            let (_request_tx, request_rx) = mpsc::channel::<Commit>(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+
+            let mut c = Cubesat::new(id, 1, result_tx, request_rx, _timer_rx, command_rx);
+            let token = token.clone();
+            handles.push(tokio::spawn(async move {
+                // Keep the channels open so that only the token can stop the Bounce unit.
+                let _channels = (_result_rx, _request_tx, _timer_tx, _command_tx);
+                c.run_until_cancelled(token).await
+            }));
+        }
+
+        token.cancel();
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), RunOutcome::Cancelled);
+        }
+    }
+
+    #[tokio::test]
+    async fn run_input_closed() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (request_tx, request_rx) = mpsc::channel::<Commit>(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        let handle = tokio::spawn(async move { c.run().await });
+
+        drop(request_tx);
+        assert_eq!(handle.await.unwrap(), RunOutcome::InputClosed);
+    }
+
+    #[tokio::test]
+    async fn run_config_invalid() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 0, result_tx, request_rx, _timer_rx, command_rx);
+        assert_eq!(c.run().await, RunOutcome::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn spawn_submit_through_handle() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+
+        let config = BounceConfig {
+            num_cubesats: 1,
+            ..Default::default()
+        };
+        let (handle, join_handle) = Cubesat::spawn(0, config, result_tx, timer_rx);
+        assert_eq!(handle.id(), 0);
+
+        // Let the Bounce unit enter the first phase before submitting the precommit.
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let ground_station_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let ground_station_public_key = Bn256
+            .derive_public_key(&ground_station_private_key)
+            .unwrap();
+        let signature = Bn256.sign(&ground_station_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 0,
+            msg: msg.clone(),
+            public_key: ground_station_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 100,
+            ..Default::default()
+        };
+        handle.clone().submit(precommit).await.unwrap();
+
+        let commit = result_rx.recv().await.unwrap();
+        assert!(!commit.aggregated);
+        let commit = result_rx.recv().await.unwrap();
+        assert!(commit.aggregated);
+        assert_eq!(commit.msg, msg);
+        Bn256
+            .verify(&commit.signature, &msg, &commit.public_key)
+            .unwrap();
+
+        handle.terminate().await.unwrap();
+        assert_eq!(join_handle.await.unwrap(), RunOutcome::Terminated);
+    }
+
+    #[tokio::test]
+    async fn full_result_channel_does_not_block_run() {
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+
+        // Fill the channel to the communications hub, which isn't drained for now.
+        result_tx.try_send(Commit::default()).unwrap();
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            ..Default::default()
+        };
+        let (handle, join_handle) = Cubesat::spawn(0, config, result_tx, timer_rx);
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let ground_station_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 0,
+            msg: msg.clone(),
+            public_key: Bn256
+                .derive_public_key(&ground_station_private_key)
+                .unwrap(),
+            signature: Bn256.sign(&ground_station_private_key, &msg).unwrap(),
+            aggregated: false,
+            signer_id: 100,
+            ..Default::default()
+        };
+        handle.submit(precommit).await.unwrap();
+        tokio::task::yield_now().await;
+
+        // Signing the precommit finds the channel full, yet the Bounce unit still terminates.
+        handle.terminate().await.unwrap();
+        let outcome = tokio::time::timeout(Duration::from_millis(500), join_handle)
+            .await
+            .expect("run loop was blocked by the full channel")
+            .unwrap();
+        assert_eq!(outcome, RunOutcome::Terminated);
+
+        // The deferred precommit is delivered once the channel is drained.
+        assert_eq!(result_rx.recv().await.unwrap(), Commit::default());
+        let commit = result_rx.recv().await.unwrap();
+        assert_eq!(commit.typ(), CommitType::Precommit);
+        assert_eq!(commit.msg, msg);
+    }
+
+    #[tokio::test]
+    async fn phase1_noncommit() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+
+        c.slot_info.phase = Phase::First;
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: 1,
+            j: 0,
+            aggregated: false,
+            public_key: Vec::new(),
+            msg: Vec::new(),
+            signature: Vec::new(),
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(noncommit).await;
+        assert!(!outcome.signed);
+        assert!(outcome.dropped.is_some());
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+    }
+
+    #[test]
+    fn public_key_of_signer() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        assert!(!c.public_key().as_bytes().is_empty());
+
+        let private_key = PrivateKey::random();
+        c.set_signer(Box::new(LocalSigner::new(private_key.clone())));
+        assert_eq!(
+            c.public_key().as_bytes(),
+            Bn256
+                .derive_public_key(private_key.as_bytes())
+                .unwrap()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn deterministic_identity() {
+        let seed = b"constellation seed";
+        let cubesat = |id, seed: &[u8]| {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (_request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+            let config = BounceConfig {
+                num_cubesats: 3,
+                ..Default::default()
+            };
+            Cubesat::deterministic(
+                id, seed, config, result_tx, request_rx, timer_rx, command_rx,
+            )
+        };
+
+        assert_eq!(cubesat(1, seed).public_key(), cubesat(1, seed).public_key());
+        assert_ne!(cubesat(1, seed).public_key(), cubesat(2, seed).public_key());
+        assert_ne!(
+            cubesat(1, seed).public_key(),
+            cubesat(1, b"another seed").public_key()
+        );
+    }
+
+    #[tokio::test]
+    async fn phase2_precommit_aggregate() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Second;
+
+        let msg = crate::precommit_message(&[], 0, 0);
+        let commits = (1..=2)
+            .map(|signer_id| signed_commit(CommitType::Precommit, 0, 0, &msg, signer_id))
+            .collect();
+
+        let aggregate = drive_to_aggregate(&mut c, commits).await;
+        assert_eq!(aggregate.typ(), CommitType::Precommit);
+        assert_eq!(aggregate.signer_count as usize, c.threshold());
+        assert_eq!(aggregate.msg, msg);
+        assert!(c.slot_info.signed);
+    }
+
+    #[tokio::test]
+    async fn phase2_commit_noncommit() {
+        // Phase 2, first send commit, then noncommit. Then the Bounce unit should sign the commit
+        // and broadcast. If it receives the noncommit right after, then bounce unit should not sign
+        // the noncommit and it only needs to keep track that it has received a noncommit.
+
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+
+        c.slot_info.phase = Phase::Second;
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.precommits.is_empty());
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert!(outcome.signed);
+        assert!(!outcome.aggregated);
+        assert_eq!(outcome.dropped, None);
+        assert!(c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+
+        let result_opt = result_rx.recv().await;
+        assert!(result_opt.is_some());
+        let commit = result_opt.unwrap();
+        assert_eq!(commit.typ(), CommitType::Precommit);
+        assert_eq!(commit.i, 0);
+        assert_eq!(commit.msg, msg);
+        assert_eq!(commit.public_key, c.public_key.as_bytes());
+
+        let cubesat2_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat2_public_key = Bn256.derive_public_key(&cubesat2_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat2_private_key, &msg).unwrap();
+
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat2_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(noncommit).await;
+        assert!(!outcome.signed);
+        assert_eq!(outcome.dropped, None);
+        assert_eq!(1, c.slot_info.noncommits.len());
+    }
+
+    #[tokio::test]
+    async fn phase2_noncommit_commit() {
+        // Similar as above, it only signs the first noncommit, and not the commit. Only keep track
+        // of the commit.
+
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+
+        c.slot_info.phase = Phase::Second;
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.precommits.is_empty());
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(noncommit).await;
+        assert!(outcome.signed);
+        assert!(!outcome.aggregated);
+        assert_eq!(outcome.dropped, None);
+        assert!(c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert_eq!(c.slot_info.noncommits.len(), 1);
+
+        let result_opt = result_rx.recv().await;
+        assert!(result_opt.is_some());
+        let commit = result_opt.unwrap();
+        assert_eq!(commit.typ(), CommitType::Noncommit);
+        assert_eq!(commit.i, 0);
+        assert_eq!(commit.msg, msg);
+        assert_eq!(commit.public_key, c.public_key.as_bytes());
+
+        let cubesat2_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat2_public_key = Bn256.derive_public_key(&cubesat2_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat2_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat2_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert!(!outcome.signed);
+        assert_eq!(outcome.dropped, None);
+        assert_eq!(1, c.slot_info.noncommits.len());
+    }
+
+    #[tokio::test]
+    async fn phase2_late_join() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        // This Bounce unit was offline during phase 1 of the slot.
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+        c.slot_info.phase = Phase::Second;
+        assert!(!c.slot_info.signed);
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::noncommit_message(&[], j, i);
+        let outcome = c
+            .process(signed_commit(CommitType::Noncommit, i, j, &msg, 1))
+            .await;
+        assert!(outcome.signed);
+        assert_eq!(outcome.dropped, None);
+
+        let signed = result_rx.try_recv().unwrap();
+        assert_eq!(signed.typ(), CommitType::Noncommit);
+        assert_eq!((signed.i, signed.signer_id), (i, 0));
+        assert_eq!(signed.msg, msg);
+        assert_eq!(c.collected_signers(), (0, 1));
+
+        // A precommit arriving next is only collected.
+        let precommit_msg = crate::precommit_message(&[], j, i);
+        let outcome = c
+            .process(signed_commit(
+                CommitType::Precommit,
+                i,
+                j,
+                &precommit_msg,
+                2,
+            ))
+            .await;
+        assert!(!outcome.signed);
+        assert!(result_rx.try_recv().is_err());
+        assert_eq!(c.collected_signers(), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn phase2_late_join_with_recorded_signature() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+        c.slot_info.phase = Phase::Second;
+
+        // A precommit of this Bounce unit was recorded in phase 1 without the slot being marked
+        // as signed.
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit_msg = crate::precommit_message(&[], j, i);
+        let own = Commit {
+            typ: CommitType::Precommit.into(),
+            i,
+            j,
+            msg: precommit_msg.clone(),
+            public_key: c.public_key.as_bytes().to_vec(),
+            signature: c.signer.sign(&precommit_msg).unwrap(),
+            signer_id: 0,
+            ..Default::default()
+        };
+        c.slot_info.precommits.push(own);
+        assert!(!c.slot_info.signed);
+
+        let msg = crate::noncommit_message(&[], j, i);
+        let outcome = c
+            .process(signed_commit(CommitType::Noncommit, i, j, &msg, 1))
+            .await;
+        assert!(!outcome.signed);
+        assert!(c.slot_info.signed);
+        assert!(result_rx.try_recv().is_err());
+        assert_eq!(c.collected_signers(), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn phase2_commit_aggregate() {
+        // Tests that in phase 2 the bounce unit aggregates signatures.
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+
+        c.slot_info.phase = Phase::Second;
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.precommits.is_empty());
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert_eq!(
+            outcome,
+            ProcessOutcome {
+                signed: true,
+                aggregated: true,
+                dropped: None,
+            }
+        );
+        assert!(c.slot_info.signed);
+        assert!(c.slot_info.aggregated);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn phase2_noncommit_aggregate() {
+        // Tests that in phase 2 the bounce unit aggregates signatures.
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+
+        c.slot_info.phase = Phase::Second;
+
+        assert!(!c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.noncommits.is_empty());
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: 0,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(noncommit).await;
+        assert_eq!(
+            outcome,
+            ProcessOutcome {
+                signed: true,
+                aggregated: true,
+                dropped: None,
+            }
+        );
+        assert!(c.slot_info.signed);
+        assert!(c.slot_info.aggregated);
+        assert_eq!(c.slot_info.noncommits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn metrics_after_aggregation() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Second;
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 0,
+            j: 0,
+            msg,
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 1,
+            ..Default::default()
+        };
+
+        assert!(c
+            .metrics_prometheus()
+            .contains("bounce_aggregations_total{id=\"0\"} 0\n"));
+
+        c.process(precommit).await;
+        assert!(c.slot_info.aggregated);
+        assert_eq!(c.metrics().commits_received, 1);
+        assert_eq!(c.metrics().signatures, 1);
+
+        let text = c.metrics_prometheus();
+        assert!(text.contains("bounce_aggregations_total{id=\"0\"} 1\n"));
+        assert!(text.contains("bounce_signatures_total{id=\"0\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn phase3_receives_precommit() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        // Assume that this Bounce unit has entered into the third phase, and signed a noncommit.
+        c.slot_info.phase = Phase::Third;
+        let msg = format!("noncommit({}, {})", c.slot_info.j + 1, c.slot_info.i);
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: c.slot_info.i,
+            j: c.slot_info.j,
+            msg: msg.clone().into_bytes(),
+            public_key: c.public_key.as_bytes().to_vec(),
+            signature: c.signer.sign(msg.as_bytes()).unwrap(),
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        c.sign_and_broadcast(noncommit.clone()).await;
+        c.slot_info.noncommits.push(noncommit);
+
+        let msg = "hello".as_bytes().to_vec();
+
+        // Then another Bounce unit sends it precommit, and the Bounce unit just keeps track of it.
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: c.slot_info.i,
+            j: c.slot_info.j,
+            msg,
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert_eq!(outcome, ProcessOutcome::default());
+
+        assert!(!c.slot_info.aggregated);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+        assert_eq!(c.slot_info.noncommits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn phase3_sign_noncommit_aggregate() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+
+        // Assume that this Bounce unit has entered into the third phase, and signed a noncommit.
+        c.slot_info.phase = Phase::Third;
+        let msg = format!("noncommit({}, {})", c.slot_info.j + 1, c.slot_info.i);
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: c.slot_info.i,
+            j: c.slot_info.j,
+            msg: msg.clone().into_bytes(),
+            public_key: c.public_key.as_bytes().to_vec(),
+            signature: c.signer.sign(msg.as_bytes()).unwrap(),
+            aggregated: false,
+            signer_id: 0,
+            ..Default::default()
+        };
+
+        c.sign_and_broadcast(noncommit.clone()).await;
+        c.slot_info.noncommits.push(noncommit);
+
+        assert!(c.slot_info.signed);
+        assert!(!c.slot_info.aggregated);
+        assert_eq!(c.slot_info.noncommits.len(), 1);
+
+        // Then another Bounce unit sends it noncommit, which results in aggregation.
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, msg.as_bytes()).unwrap();
+
+        let noncommit = Commit {
+            typ: CommitType::Noncommit.into(),
+            i: c.slot_info.i,
+            j: c.slot_info.j,
+            msg: msg.into_bytes(),
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 1,
+            ..Default::default()
+        };
+
+        let outcome = c.process(noncommit).await;
+        assert!(!outcome.signed);
+        assert!(outcome.aggregated);
+        assert_eq!(outcome.dropped, None);
+        assert!(c.slot_info.signed);
+        assert!(c.slot_info.aggregated);
+        assert_eq!(c.slot_info.noncommits.len(), 2);
+    }
+
+    #[test]
+    fn aggregate_rejects_duplicate_commit() {
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 0,
+            msg,
+            public_key: cubesat1_public_key,
+            signature,
+            aggregated: false,
+            signer_id: 1,
+            ..Default::default()
+        };
+
+        // The same commit pushed twice must not produce an aggregate claiming two signers.
+        match Cubesat::aggregate(&[precommit.clone(), precommit]) {
+            Err(AggregateError::SignerCountMismatch {
+                public_keys,
+                signers,
+            }) => {
+                assert_eq!(public_keys, 2);
+                assert_eq!(signers, 1);
+            }
+            other => panic!("expected SignerCountMismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregate_k_of_n() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        // Supermajority of 4 is 3: this Bounce unit and two others.
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::First;
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        for signer_id in 1..4 {
+            let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+            let precommit = Commit {
+                typ: CommitType::Precommit.into(),
+                i: 0,
+                j: 0,
+                msg: msg.clone(),
+                public_key: Bn256.derive_public_key(&private_key).unwrap(),
+                signature: Bn256.sign(&private_key, &msg).unwrap(),
+                aggregated: false,
+                signer_id,
+                ..Default::default()
+            };
+            c.process(precommit).await;
+        }
+        assert!(c.slot_info.aggregated);
+
+        // The first result is this unit's own precommit, then the aggregate.
+        let _ = result_rx.recv().await.unwrap();
+        let aggregate = result_rx.recv().await.unwrap();
+        assert!(aggregate.aggregated);
+        assert_eq!(aggregate.signer_count, 3);
+
+        assert_eq!(crate::verify_aggregate(&aggregate, 3), Ok(()));
+        assert_eq!(
+            crate::verify_aggregate(&aggregate, 4),
+            Err(crate::VerifyError::InsufficientSigners {
+                claimed: 3,
+                required: 4
+            })
+        );
+    }
+
+    #[test]
+    fn threshold_from_registry() {
+        let (result_tx, _result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        assert_eq!(c.threshold(), supermajority(4));
+
+        let mut registry = PeerRegistry::new();
+        for id in 0..7 {
+            registry.insert(id, vec![id as u8]);
+        }
+        c.set_registry(registry);
+        assert_eq!(c.threshold(), supermajority(7));
+    }
+
+    #[tokio::test]
+    async fn aggregate_carries_canonical_message() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+        assert_eq!(c.slot_info.phase, Phase::First);
+
+        let (j, i) = (c.slot_info.j, c.slot_info.i);
+        let msg = crate::precommit_message(&[], j, i);
+
+        let mut rng = thread_rng();
+        for signer_id in 1..3 {
+            let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+            let precommit = Commit {
+                typ: CommitType::Precommit.into(),
+                i,
+                j,
+                msg: msg.clone(),
+                public_key: Bn256.derive_public_key(&private_key).unwrap(),
+                signature: Bn256.sign(&private_key, &msg).unwrap(),
+                aggregated: false,
+                signer_id,
+                ..Default::default()
+            };
+            c.process(precommit).await;
+        }
+
+        let _ = result_rx.recv().await.unwrap();
+        let aggregate = result_rx.recv().await.unwrap();
+        assert!(aggregate.aggregated);
+        assert_eq!(aggregate.typ(), CommitType::Precommit);
+        assert_eq!(aggregate.msg, crate::precommit_message(&[], j, i));
+        assert_eq!(aggregate.j, i);
+        assert_eq!(crate::verify_aggregate(&aggregate, 2), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn process_batch_single_aggregate() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        // Supermajority of 4 is 3.
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Second;
+
+        let msg = "hello".as_bytes().to_vec();
+
+        let mut rng = thread_rng();
+        let mut commits = Vec::new();
+        for signer_id in 1..5 {
+            let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+            commits.push(Commit {
+                typ: CommitType::Precommit.into(),
+                i: 0,
+                j: 0,
+                msg: msg.clone(),
+                public_key: Bn256.derive_public_key(&private_key).unwrap(),
+                signature: Bn256.sign(&private_key, &msg).unwrap(),
+                aggregated: false,
+                signer_id,
+                ..Default::default()
+            });
+        }
+        // A redelivered commit is only collected once.
+        commits.push(commits[2].clone());
+
+        c.process_batch(commits).await;
+        assert!(c.slot_info.aggregated);
+        assert_eq!(c.slot_info.precommits.len(), 4);
+        assert_eq!(c.metrics().aggregations, 1);
+
+        let commit = result_rx.recv().await.unwrap();
+        assert!(!commit.aggregated);
+        let aggregate = result_rx.recv().await.unwrap();
+        assert!(aggregate.aggregated);
+        assert_eq!(aggregate.signer_count, 4);
+        assert!(result_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn process_batch_verify_threads() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let config = BounceConfig {
+            num_cubesats: 7,
+            verify_threads: 2,
+            ..Default::default()
+        };
+
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Third;
+
+        let msg = noncommit_message(&[], 0, 0);
+        let mut commits: Vec<Commit> = (1..=4)
+            .map(|signer_id| signed_commit(CommitType::Noncommit, 0, 0, &msg, signer_id))
+            .collect();
+        commits[1].signature = commits[2].signature.clone();
+
+        c.process_batch(commits).await;
+        assert_eq!(c.slot_info.noncommits.len(), 3);
+        assert_eq!(c.metrics().commits_dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn process_batch_yields() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let config = BounceConfig {
+            num_cubesats: 100,
+            verify_yield_every: 4,
+            ..Default::default()
+        };
+
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Third;
+
+        let msg = noncommit_message(&[], 0, 0);
+        let commits: Vec<Commit> = (1..=40)
+            .map(|signer_id| signed_commit(CommitType::Noncommit, 0, 0, &msg, signer_id))
+            .collect();
+
+        // On this single-threaded runtime, the task only runs if the batch yields.
+        let metrics = c.metrics_handle();
+        let observer = tokio::spawn(async move { metrics.snapshot().commits_received });
+
+        c.process_batch(commits).await;
+        assert_eq!(c.metrics().commits_received, 40);
+        assert_eq!(observer.await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn key_change_policy() {
+        for policy in [KeyChangePolicy::Accept, KeyChangePolicy::Reject].iter() {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (_request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+            let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+            let mut config = BounceConfig {
+                num_cubesats: 7,
+                ..Default::default()
+            };
+            config.set_key_change_policy(*policy);
+
+            let mut c =
+                Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+            c.set_event_sender(events_tx);
+            c.slot_info.phase = Phase::Third;
+
+            let msg = noncommit_message(&[], 0, 0);
+            let key_a = signed_commit(CommitType::Noncommit, 0, 0, &msg, 1);
+            let key_b = signed_commit(CommitType::Noncommit, 0, 0, &msg, 1);
+
+            assert_eq!(c.process(key_a.clone()).await.dropped, None);
+            assert_eq!(c.learned_keys().get(1), Some(key_a.public_key.as_slice()));
+
+            let accepted = *policy == KeyChangePolicy::Accept;
+            let outcome = c.process(key_b.clone()).await;
+            if accepted {
+                assert_eq!(outcome.dropped, None);
+                assert_eq!(c.learned_keys().get(1), Some(key_b.public_key.as_slice()));
+            } else {
+                assert_eq!(outcome.dropped, Some(DropReason::KeyChanged));
+                assert_eq!(c.learned_keys().get(1), Some(key_a.public_key.as_slice()));
+            }
+            assert_eq!(
+                events_rx.try_recv().unwrap(),
+                Event::KeyChanged {
+                    signer_id: 1,
+                    previous: key_a.public_key,
+                    public_key: key_b.public_key,
+                    accepted,
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rotated_signer_counted_once() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let mut config = BounceConfig {
+            num_cubesats: 4,
+            ..Default::default()
+        };
+        config.set_key_change_policy(KeyChangePolicy::Accept);
+
+        // Supermajority of 4 is 3.
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+
+        // Signing the first precommit collects the own one.
+        c.process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await;
+        c.process(signed_commit(CommitType::Precommit, i, j, &msg, 2))
+            .await;
+
+        // Signer 2 rotates its key: it replaces its commit rather than counting twice.
+        let rotated = signed_commit(CommitType::Precommit, i, j, &msg, 2);
+        let outcome = c.process(rotated.clone()).await;
+        assert_eq!(outcome.dropped, None);
+        assert!(!outcome.aggregated);
+        assert_eq!(c.slot_info.precommits.len(), 2);
+        assert!(c.slot_info.precommits.contains(&rotated));
+
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &msg, 3))
+            .await;
+        assert!(outcome.aggregated);
+        let signed = result_rx.recv().await.unwrap();
+        assert!(!signed.aggregated);
+        let aggregate = result_rx.recv().await.unwrap();
+        assert_eq!(aggregate.signers, vec![0, 2, 3]);
+        assert_eq!(verify_aggregate(&aggregate, 3), Ok(()));
+    }
+
+    #[test]
+    fn aggregate_is_order_independent() {
+        let msg = crate::precommit_message(&[], 0, 1);
+        let commits: Vec<Commit> = (1..=3)
+            .map(|signer_id| signed_commit(CommitType::Precommit, 1, 0, &msg, signer_id))
+            .collect();
+        let reordered = vec![commits[2].clone(), commits[0].clone(), commits[1].clone()];
+
+        assert_eq!(
+            Cubesat::aggregate(&commits).unwrap(),
+            Cubesat::aggregate(&reordered).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn leader_initiates_precommit() {
+        let config = BounceConfig {
+            num_cubesats: 4,
+            leader: Some(0),
+            ..Default::default()
+        };
+        let (result_tx, mut result_rx) = mpsc::channel(100);
+        let (timer_tx, _timer_rx) = broadcast::channel(15);
+        let handles: Vec<_> = (0..4)
+            .map(|id| Cubesat::spawn(id, config.clone(), result_tx.clone(), timer_tx.subscribe()).0)
+            .collect();
+
+        // No ground station: the only input besides the relayed results is the phase transition.
+        timer_tx.send(Phase::First).unwrap();
+        let aggregate = loop {
+            let commit = result_rx.recv().await.unwrap();
+            if commit.aggregated {
+                break commit;
+            }
+            for handle in handles.iter() {
+                handle.submit(commit.clone()).await.unwrap();
+            }
+        };
+        for handle in handles.iter() {
+            handle.terminate().await.unwrap();
+        }
+
+        assert_eq!(aggregate.typ(), CommitType::Precommit);
+        assert_eq!(aggregate.i, 1);
+        assert_eq!(aggregate.msg, crate::precommit_message(&[], 0, 1));
+        assert_eq!(
+            crate::verify_aggregate(&aggregate, supermajority(4)),
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn finalized_stream_across_slots() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        let mut finalized = c.finalized_stream();
+
+        let mut aggregates = Vec::new();
+        for _ in 0..2 {
+            c.enter_phase(Phase::First).await;
+            let (i, j) = (c.slot_info.i, c.slot_info.j);
+            let aggregate = aggregate_commit(
+                CommitType::Precommit,
+                i,
+                i,
+                &crate::precommit_message(&[], j, i),
+                2,
+            );
+            assert!(c.process(aggregate.clone()).await.aggregated);
+            aggregates.push(aggregate);
+        }
+
+        let streamed: Vec<Commit> = (&mut finalized).take(2).collect().await;
+        assert_eq!(streamed, aggregates);
+    }
+
+    #[tokio::test]
+    async fn partial_checkpoints() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let config = BounceConfig {
+            num_cubesats: 7,
+            checkpoint_every: 2,
+            ..Default::default()
+        };
+
+        // Supermajority of 7 is 5.
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.phase = Phase::Third;
+
+        let msg = noncommit_message(&[], 0, 0);
+        let mut partials = Vec::new();
+        for signer_id in 1..=4 {
+            c.process(signed_commit(CommitType::Noncommit, 0, 0, &msg, signer_id))
+                .await;
+            if let Ok(commit) = result_rx.try_recv() {
+                partials.push((signer_id, commit));
+            }
+        }
+        assert!(!c.slot_info.aggregated);
+
+        assert_eq!(partials.len(), 2);
+        for (collected, partial) in partials.iter() {
+            assert!(partial.partial);
+            assert!(partial.aggregated);
+            assert_eq!(partial.signer_count, *collected);
+            assert_eq!(
+                crate::verify_aggregate(partial, *collected as usize),
+                Ok(())
+            );
+        }
+        assert_eq!(partials[0].0, 2);
+        assert_eq!(partials[1].0, 4);
+
+        // Checkpoints never finalize a slot.
+        let outcome = c.process(partials[1].1.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::PartialAggregate));
+    }
+
+    #[tokio::test]
+    async fn conflicting_aggregates_reported() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.slot_info.next();
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = aggregate_commit(
+            CommitType::Precommit,
+            i,
+            i,
+            &crate::precommit_message(&[], j, i),
+            2,
+        );
+        let noncommit = aggregate_commit(
+            CommitType::Noncommit,
+            i,
+            j,
+            &noncommit_message(&[], j, i),
+            2,
+        );
+
+        let outcome = c.process(precommit.clone()).await;
+        assert!(outcome.aggregated);
+        assert!(c.slot_info.aggregated);
+        assert!(events_rx.try_recv().is_err());
+
+        // Redelivering the accepted aggregate isn't a conflict.
+        let outcome = c.process(precommit.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::AlreadyAggregated));
+        assert!(events_rx.try_recv().is_err());
+
+        let outcome = c.process(noncommit.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::AlreadyAggregated));
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            Event::SafetyViolation {
+                i,
+                accepted: precommit.clone(),
+                conflicting: noncommit,
+            }
+        );
+        assert_eq!(c.slot_info.aggregate, Some(precommit));
+        assert_eq!(c.slot_info.j, i);
+    }
+
+    #[tokio::test]
+    async fn aggregate_retried_until_acknowledged() {
+        time::pause();
+        let (result_tx, mut result_rx) = mpsc::channel(15);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            aggregate_max_retries: 3,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        let interval = c.min_aggregate_interval();
+        let task = tokio::spawn(async move {
+            c.run_until_cancelled(CancellationToken::new()).await;
+        });
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        let msg = crate::precommit_message(&[], 0, 1);
+        for id in 1..4 {
+            request_tx
+                .send(signed_commit(CommitType::Precommit, 1, 0, &msg, id))
+                .await
+                .unwrap();
+        }
+
+        // A lossy hub drops the first broadcast of the aggregate.
+        let aggregate = loop {
+            let commit = result_rx.recv().await.unwrap();
+            if commit.aggregated {
+                break commit;
+            }
+        };
+        time::sleep(interval / 2).await;
+        assert!(result_rx.try_recv().is_err());
+
+        // The retry gets through, so that a peer finalizes the slot.
+        time::sleep(interval).await;
+        let retried = result_rx.try_recv().unwrap();
+        assert_eq!(retried, aggregate);
+
+        let (peer_tx, _peer_rx) = mpsc::channel(5);
+        let (_, peer_request_rx) = mpsc::channel(1);
+        let (_, peer_timer_rx) = broadcast::channel(1);
+        let (_, peer_command_rx) = mpsc::channel(1);
+        let mut peer = Cubesat::new(
+            1,
+            4,
+            peer_tx,
+            peer_request_rx,
+            peer_timer_rx,
+            peer_command_rx,
+        );
+        peer.slot_info.next();
+        assert!(peer.process(retried.clone()).await.aggregated);
+
+        // Once the hub echoes it back, retries stop.
+        request_tx.send(retried).await.unwrap();
+        time::sleep(interval * 5).await;
+        assert!(result_rx.try_recv().is_err());
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn aggregate_retries_bounded() {
+        time::pause();
+        let (result_tx, mut result_rx) = mpsc::channel(15);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            aggregate_max_retries: 2,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        c.slot_info.next();
+        let msg = crate::precommit_message(&[], 0, 1);
+        let commits: Vec<Commit> = (1..4)
+            .map(|id| signed_commit(CommitType::Precommit, 1, 0, &msg, id))
+            .collect();
+        let aggregate = drive_to_aggregate(&mut c, commits).await;
+        let interval = c.min_aggregate_interval();
+
+        let mut retries = 0;
+        for _ in 0..5 {
+            time::sleep(interval).await;
+            c.retry_aggregate_if_due().await;
+        }
+        while let Ok(commit) = result_rx.try_recv() {
+            if commit == aggregate {
+                retries += 1;
+            }
+        }
+        // The initial broadcast and two retries.
+        assert_eq!(retries, 3);
+    }
+
+    #[tokio::test]
+    async fn competing_aggregates_converge() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let mut cubesats: Vec<Cubesat> = (0..2)
+            .map(|id| {
+                let (_request_tx, request_rx) = mpsc::channel(15);
+                let (_timer_tx, timer_rx) = broadcast::channel(15);
+                let (_command_tx, command_rx) = mpsc::channel(1);
+                let mut c =
+                    Cubesat::new(id, 4, result_tx.clone(), request_rx, timer_rx, command_rx);
+                c.slot_info.next();
+                c
+            })
+            .collect();
+
+        // Two supermajorities of the same precommit, by signers 0 to 2 and 1 to 3.
+        let (i, j) = (cubesats[0].slot_info.i, cubesats[0].slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let commits: Vec<Commit> = (0..4)
+            .map(|id| signed_commit(CommitType::Precommit, i, i, &msg, id))
+            .collect();
+        let aggregate_of = |commits: &[Commit]| {
+            let (signature, public_key) = Cubesat::aggregate(commits).unwrap();
+            Commit {
+                typ: CommitType::Precommit.into(),
+                i,
+                j: i,
+                msg: msg.clone(),
+                public_key,
+                signature,
+                aggregated: true,
+                signer_count: commits.len() as u32,
+                signers: commits.iter().map(|c| c.signer_id).collect(),
+                ..Default::default()
+            }
+        };
+        let (first, last) = (aggregate_of(&commits[..3]), aggregate_of(&commits[1..]));
+
+        // Whatever the order they arrive in, the aggregate without signer 0 is adopted.
+        cubesats[0].process(first.clone()).await;
+        cubesats[0].process(last.clone()).await;
+        cubesats[1].process(last.clone()).await;
+        cubesats[1].process(first.clone()).await;
+        for c in cubesats.iter() {
+            assert_eq!(c.slot_info.aggregate, Some(last.clone()));
+        }
+        assert!(signer_bitmap(&last.signers) < signer_bitmap(&first.signers));
+    }
+
+    #[tokio::test]
+    async fn noncommit_in_phase1_reports_desync() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.enter_phase(Phase::First).await;
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let noncommit = signed_commit(
+            CommitType::Noncommit,
+            i,
+            j,
+            &noncommit_message(&[], j, i),
+            1,
+        );
+
+        let outcome = c.process(noncommit).await;
+        assert_eq!(outcome.dropped, Some(DropReason::WrongPhase));
+        assert!(c.slot_info.noncommits.is_empty());
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            Event::PhaseDesync {
+                i,
+                phase: Phase::First,
+                typ: CommitType::Noncommit,
+                signer_id: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn rotate_epoch_key() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let config = BounceConfig {
+            num_cubesats: 3,
+            epoch_slots: 2,
+            ..Default::default()
+        };
+        let master = b"master seed".to_vec();
+
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        c.set_epoch_master(master.clone());
+
+        let key0 = LocalSigner::new(derive_epoch_key(&master, 0)).public_key();
+        assert_eq!(c.epoch(), Some(0));
+        assert_eq!(c.public_key, key0);
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            Event::EpochKey {
+                epoch: 0,
+                public_key: key0.clone()
+            }
+        );
+
+        // Slot 1 is still in epoch 0, slot 2 starts epoch 1.
+        c.enter_phase(Phase::First).await;
+        assert_eq!(c.epoch(), Some(0));
+        assert!(events_rx.try_recv().is_err());
+
+        c.enter_phase(Phase::First).await;
+        let key1 = LocalSigner::new(derive_epoch_key(&master, 1)).public_key();
+        assert_eq!(c.epoch(), Some(1));
+        assert_eq!(c.public_key, key1);
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            Event::EpochKey {
+                epoch: 1,
+                public_key: key1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_commit_from_other_domain() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            domain: b"B".to_vec(),
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        let mut rng = thread_rng();
+        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
+
+        // A precommit signed under domain A must not be accepted by a unit of domain B.
+        let msg = crate::precommit_message(b"A", 0, 1);
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key.clone(),
+            signature: Bn256.sign(&cubesat1_private_key, &msg).unwrap(),
+            aggregated: false,
+            signer_id: 1,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert_eq!(outcome.dropped, Some(DropReason::WrongDomain));
+        assert!(!c.slot_info.signed);
+        assert!(c.slot_info.precommits.is_empty());
+
+        // The same precommit signed under domain B is accepted.
+        let msg = crate::precommit_message(b"B", 0, 1);
+        let precommit = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 0,
+            msg: msg.clone(),
+            public_key: cubesat1_public_key,
+            signature: Bn256.sign(&cubesat1_private_key, &msg).unwrap(),
+            aggregated: false,
+            signer_id: 1,
+            ..Default::default()
+        };
+
+        let outcome = c.process(precommit).await;
+        assert!(outcome.signed);
+        assert_eq!(outcome.dropped, None);
+        assert!(c.slot_info.signed);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn history_of_three_slots() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+
+        // Slot 1 finalizes with a precommit aggregate.
+        c.slot_info.next();
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = aggregate_commit(
+            CommitType::Precommit,
+            i,
+            i,
+            &crate::precommit_message(&[], j, i),
+            2,
+        );
+        assert!(c.process(precommit).await.aggregated);
+
+        // Slot 2 finalizes with a noncommit aggregate in the third phase.
+        c.slot_info.next();
+        c.slot_info.phase = Phase::Third;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let noncommit = aggregate_commit(
+            CommitType::Noncommit,
+            i,
+            j,
+            &noncommit_message(&[], j, i),
+            2,
+        );
+        assert!(c.process(noncommit).await.aggregated);
+
+        // Slot 3 collects a single precommit and doesn't finalize.
+        c.slot_info.next();
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, i),
+            1,
+        );
+        assert!(!c.process(precommit).await.aggregated);
+        c.slot_info.next();
+
+        let history = c.history();
+        assert_eq!(history.len(), 3);
+
+        let slot1 = history.get(1).unwrap();
+        assert!(slot1.finalized);
+        assert_eq!(slot1.outcome, Some(CommitType::Precommit));
+        assert_eq!(slot1.j, 1);
+
+        let slot2 = history.get(2).unwrap();
+        assert!(slot2.finalized);
+        assert_eq!(slot2.outcome, Some(CommitType::Noncommit));
+        assert_eq!(slot2.phase, Phase::Third);
+        assert_eq!(slot2.j, 1);
+
+        let slot3 = history.get(3).unwrap();
+        assert!(!slot3.finalized);
+        assert_eq!(slot3.outcome, None);
+        assert_eq!(slot3.precommits, 1);
+    }
+
+    #[tokio::test]
+    async fn observer_does_not_sign() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            observer: true,
+            ..Default::default()
         };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        assert!(c.is_observer());
+        c.slot_info.next();
 
-        tokio::spawn(async move {
-            request_tx
-                .send(precommit)
-                .await
-                .expect("failed to send precommit");
+        // A single precommit is tracked but not signed.
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await;
+        assert!(!outcome.signed);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+
+        // Even with a supermajority of precommits, the observer doesn't aggregate.
+        c.process(signed_commit(CommitType::Precommit, i, j, &msg, 2))
+            .await;
+        assert!(!c.slot_info.aggregated);
+
+        let precommit = aggregate_commit(CommitType::Precommit, i, i, &msg, 2);
+        let outcome = c.process(precommit.clone()).await;
+        assert!(outcome.aggregated);
+        assert!(!c.slot_info.signed);
+        assert_eq!(c.slot_info.aggregate, Some(precommit));
+        assert_eq!(c.slot_info.j, i);
+        assert!(result_rx.try_recv().is_err());
+    }
+
+    // Signer failing with a transient error until it has been called `failures` times.
+    struct FlakySigner {
+        signer: LocalSigner,
+        failures: u32,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl Signer for FlakySigner {
+        fn public_key(&self) -> PublicKey {
+            self.signer.public_key()
+        }
+
+        fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, SignerError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(SignerError::Transient("busy".to_owned()));
+            }
+            self.signer.sign(msg)
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_retries_transient_errors() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        let calls = Arc::new(AtomicU32::new(0));
+        let signer = LocalSigner::random();
+        let public_key = signer.public_key();
+        c.set_signer(Box::new(FlakySigner {
+            signer,
+            failures: 2,
+            calls: calls.clone(),
+        }));
+        c.slot_info.next();
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await;
+        assert!(outcome.signed);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let commit = result_rx.recv().await.unwrap();
+        assert_eq!(commit.public_key, public_key.as_bytes());
+        assert!(Bn256
+            .verify(&commit.signature, &msg, public_key.as_bytes())
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn phase_change_during_process() {
+        time::pause();
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, timer_rx, command_rx);
+        let calls = Arc::new(AtomicU32::new(0));
+        c.set_signer(Box::new(FlakySigner {
+            signer: LocalSigner::random(),
+            failures: 1,
+            calls: calls.clone(),
+        }));
+        c.enter_phase(Phase::First).await;
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let task = tokio::spawn(async move {
+            c.run().await;
+            c
         });
 
-        let result_opt = result_rx.recv().await;
-        assert!(result_opt.is_some());
-        let commit = result_opt.unwrap();
+        // Phase 2 starts while the Bounce unit waits to retry signing the phase 1 precommit.
+        request_tx
+            .send(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        timer_tx.send(Phase::Second).unwrap();
 
+        let commit = result_rx.recv().await.unwrap();
         assert_eq!(commit.typ(), CommitType::Precommit);
-        assert_eq!(commit.i, 0);
-        assert_eq!(commit.msg, msg);
-        assert!(!commit.aggregated);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
 
-        let result_opt = result_rx.recv().await;
-        assert!(result_opt.is_some());
-        let commit = result_opt.unwrap();
+        drop(request_tx);
+        let c = task.await.unwrap();
+        assert_eq!(c.slot_info.phase, Phase::Second);
+        assert_eq!(c.slot_info.i, i);
+        assert!(c.slot_info.signed);
+        assert_eq!(c.slot_info.precommits.len(), 1);
+    }
 
-        assert_eq!(commit.typ(), CommitType::Precommit);
-        assert_eq!(commit.i, 0);
-        assert_eq!(commit.msg, msg);
-        assert!(commit.aggregated);
+    #[tokio::test]
+    async fn aggregate_refuses_mixed_messages() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let _ = Bn256
-            .verify(&commit.signature, &msg, &commit.public_key)
-            .unwrap();
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message_with_payload(&[], j, i, b"block-a");
+        let other_msg = crate::precommit_message_with_payload(&[], j, i, b"block-b");
+        let precommits = vec![
+            signed_commit(CommitType::Precommit, i, j, &other_msg, 1),
+            signed_commit(CommitType::Precommit, i, j, &msg, 2),
+            signed_commit(CommitType::Precommit, i, j, &msg, 3),
+        ];
+        assert!(matches!(
+            expected_aggregate(&precommits),
+            Err(AggregateError::MessageMismatch { messages: 2 })
+        ));
+
+        // A set split between messages aggregates the commits agreeing on the majority message.
+        c.slot_info.precommits = precommits;
+        c.aggregate_if_supermajority().await;
+        let aggregate = result_rx.try_recv().unwrap();
+        assert_eq!(aggregate.msg, msg);
+        assert_eq!(aggregate.signers, vec![2, 3]);
+        assert_eq!(verify_aggregate(&aggregate, 2), Ok(()));
+        assert_eq!(c.metrics().commits_dropped, 1);
     }
 
     #[tokio::test]
-    async fn phase1_noncommit() {
-        let (result_tx, _result_rx) = mpsc::channel(1);
+    async fn aggregate_excludes_other_payload() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx);
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
 
-        c.slot_info.phase = Phase::First;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let payload = b"block-a".to_vec();
+        let msg = crate::precommit_message_with_payload(&[], j, i, &payload);
+        let other_msg = crate::precommit_message_with_payload(&[], j, i, b"block-b");
 
-        assert!(!c.slot_info.signed);
+        let commit = |msg: &[u8], payload: &[u8], signer_id| Commit {
+            payload: payload.to_vec(),
+            ..signed_commit(CommitType::Precommit, i, j, msg, signer_id)
+        };
+
+        let outcome = c.process(commit(&msg, &payload, 1)).await;
+        assert!(outcome.signed);
+
+        let outcome = c.process(commit(&other_msg, b"block-b", 2)).await;
+        assert_eq!(outcome.dropped, Some(DropReason::MessageMismatch));
         assert!(!c.slot_info.aggregated);
 
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: 1,
-            j: 0,
-            aggregated: false,
-            public_key: Vec::new(),
-            msg: Vec::new(),
-            signature: Vec::new(),
-            signer_id: 0,
+        let outcome = c.process(commit(&msg, &payload, 3)).await;
+        assert!(outcome.aggregated);
+
+        let signed = result_rx.recv().await.unwrap();
+        assert_eq!(signed.payload, payload);
+        let aggregate = result_rx.recv().await.unwrap();
+        assert_eq!(aggregate.msg, msg);
+        assert_eq!(aggregate.payload, payload);
+        assert!(!aggregate.signers.contains(&2));
+        assert_eq!(crate::verify_aggregate(&aggregate, 2), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn heartbeat() {
+        time::pause();
+
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            heartbeat_interval_ms: 1_000,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        let public_key = c.public_key().as_bytes().to_vec();
+        tokio::spawn(async move { c.run().await });
+        timer_tx.send(Phase::First).unwrap();
+
+        time::advance(Duration::from_millis(999)).await;
+        assert!(result_rx.try_recv().is_err());
+        time::advance(Duration::from_millis(1)).await;
+        let heartbeat = result_rx.recv().await.unwrap();
+        assert_eq!(heartbeat.typ(), CommitType::Heartbeat);
+        assert_eq!(heartbeat.i, 1);
+        assert_eq!(heartbeat.public_key, public_key);
+        assert!(verify_signature(&heartbeat));
+
+        // The next one follows an interval later.
+        time::advance(Duration::from_millis(1_000)).await;
+        assert_eq!(result_rx.recv().await.unwrap().typ(), CommitType::Heartbeat);
+
+        // A peer's heartbeat counts towards no aggregate, nor does it prompt signing.
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let mut peer = Cubesat::new(1, 3, result_tx, request_rx, _timer_rx, command_rx);
+        peer.enter_phase(Phase::First).await;
+        let outcome = peer.process(heartbeat).await;
+        assert_eq!(outcome.dropped, Some(DropReason::Heartbeat));
+        assert!(!outcome.signed);
+        assert_eq!(peer.collected_signers(), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn unknown_commit_type() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let mut commit = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, i),
+            1,
+        );
+        commit.typ = 7;
+        let outcome = c.process(commit).await;
+        assert_eq!(outcome.dropped, Some(DropReason::UnknownType));
+        assert!(!outcome.signed);
+    }
+
+    #[tokio::test]
+    async fn unsupported_version() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let mut future = signed_commit(CommitType::Precommit, i, j, &msg, 1);
+        future.version = SIGNATURE_SCHEME_VERSION + 1;
+        let outcome = c.process(future).await;
+        assert_eq!(outcome.dropped, Some(DropReason::UnsupportedVersion));
+        assert!(!outcome.signed);
+
+        let mut current = signed_commit(CommitType::Precommit, i, j, &msg, 2);
+        current.version = SIGNATURE_SCHEME_VERSION;
+        let outcome = c.process(current).await;
+        assert_eq!(outcome.dropped, None);
+        assert!(outcome.signed);
+    }
+
+    #[tokio::test]
+    async fn non_canonical_message() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        for _ in 0..3 {
+            c.enter_phase(Phase::First).await;
+        }
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        assert_eq!(i, 3);
+
+        // The message is the one of slot 5, validly signed, but the commit claims slot 3.
+        let spoofed = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, 5),
+            1,
+        );
+        let outcome = c.process(spoofed).await;
+        assert_eq!(outcome.dropped, Some(DropReason::NonCanonicalMessage));
+        assert!(!outcome.signed);
+        assert_eq!(c.metrics().commits_dropped, 1);
+
+        let honest = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, i),
+            1,
+        );
+        let outcome = c.process(honest).await;
+        assert_eq!(outcome.dropped, None);
+        assert!(outcome.signed);
+    }
+
+    #[tokio::test]
+    async fn canonical_messages_only() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            canonical_messages_only: true,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+
+        // A slot message with trailing bytes the commit's payload doesn't account for.
+        let mut extended = crate::precommit_message(&[], j, i);
+        extended.extend_from_slice(b", ab");
+        for msg in [b"hello".to_vec(), extended.clone()].iter() {
+            let outcome = c
+                .process(signed_commit(CommitType::Precommit, i, j, msg, 1))
+                .await;
+            assert_eq!(outcome.dropped, Some(DropReason::NonCanonicalMessage));
+        }
+
+        // Without the option, free-form messages still pass, but not extended slot messages.
+        c.config.canonical_messages_only = false;
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, b"hello", 1))
+            .await;
+        assert!(outcome.signed);
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &extended, 2))
+            .await;
+        assert_eq!(outcome.dropped, Some(DropReason::NonCanonicalMessage));
+    }
+
+    #[tokio::test]
+    async fn process_bytes_rejects_garbage() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        // An invalid wire type can't be decoded.
+        let outcome = c.process_bytes(&[0xff; 16]).await;
+        assert_eq!(outcome.dropped, Some(DropReason::Decode));
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let commit = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, i),
+            1,
+        );
+        let mut raw = Vec::new();
+        commit.encode(&mut raw).unwrap();
+
+        let outcome = c.process_bytes(&raw[..raw.len() - 1]).await;
+        assert_eq!(outcome.dropped, Some(DropReason::Decode));
+        assert_eq!(c.metrics().commits_dropped, 2);
+
+        // Random input either fails to decode or is dropped as an invalid commit.
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let len = rng.gen_range(0, 64);
+            let raw: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let outcome = c.process_bytes(&raw).await;
+            assert!(outcome.dropped.is_some());
+        }
+
+        let outcome = c.process_bytes(&raw).await;
+        assert!(outcome.signed);
+    }
+
+    #[tokio::test]
+    async fn noncommit_delayed_by_clock_skew_tolerance() {
+        time::pause();
+
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let tolerance = Duration::from_millis(500);
+        let config = BounceConfig {
+            num_cubesats: 3,
+            clock_skew_tolerance_ms: 500,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        assert_eq!(c.clock_skew_tolerance(), tolerance);
+        tokio::spawn(async move { c.run().await });
+
+        timer_tx.send(Phase::First).unwrap();
+        timer_tx.send(Phase::Second).unwrap();
+        timer_tx.send(Phase::Third).unwrap();
+        let phase3_start = Instant::now();
+        tokio::task::yield_now().await;
+
+        time::advance(tolerance - Duration::from_millis(1)).await;
+        assert!(result_rx.try_recv().is_err());
+
+        let noncommit = result_rx.recv().await.unwrap();
+        assert_eq!(noncommit.typ(), CommitType::Noncommit);
+        // The timer has a granularity of a millisecond.
+        let elapsed = Instant::now() - phase3_start;
+        assert!(elapsed >= tolerance && elapsed <= tolerance + Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn first_phase_only() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut config = BounceConfig {
+            num_cubesats: 3,
+            ..Default::default()
+        };
+        config.set_enabled_phases(PhaseSet::FIRST_ONLY);
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        let handle = tokio::spawn(async move {
+            c.run_until_cancelled(CancellationToken::new()).await;
+        });
+
+        // A timer unaware of the disabled phases still sends them.
+        for _ in 0..2 {
+            for phase in [Phase::First, Phase::Second, Phase::Third].iter() {
+                timer_tx.send(phase.clone()).unwrap();
+                tokio::task::yield_now().await;
+            }
+        }
+        assert!(result_rx.try_recv().is_err());
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn noncommit_withheld_during_startup_grace() {
+        time::pause();
+
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            slot_duration: 10,
+            phase1_duration: 4,
+            phase2_duration: 3,
+            startup_grace_ms: 3_000,
+            ..Default::default()
         };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        assert_eq!(c.startup_grace(), Duration::from_secs(3));
+        tokio::spawn(async move { c.run().await });
+
+        // Started 1.5s into phase 3, the Bounce unit catches up with the current slot.
+        timer_tx.send(Phase::First).unwrap();
+        timer_tx.send(Phase::Second).unwrap();
+        timer_tx.send(Phase::Third).unwrap();
+        time::advance(Duration::from_millis(1_500)).await;
+        assert!(result_rx.try_recv().is_err());
+
+        // The next slot is the first it takes part in from the start.
+        timer_tx.send(Phase::First).unwrap();
+        time::advance(Duration::from_secs(4)).await;
+        timer_tx.send(Phase::Second).unwrap();
+        time::advance(Duration::from_secs(3)).await;
+        assert!(result_rx.try_recv().is_err());
+
+        timer_tx.send(Phase::Third).unwrap();
+        let noncommit = result_rx.recv().await.unwrap();
+        assert_eq!(noncommit.typ(), CommitType::Noncommit);
+        assert_eq!(noncommit.i, 2);
+    }
+
+    #[tokio::test]
+    async fn unfinalized_slots_withheld() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        // Start taking part in the protocol from slot 2.
+        c.slot_info.i = 1;
+
+        for i in 2..=4 {
+            c.slot_info.next();
+            assert_eq!(c.slot_info.i, i);
+
+            // Commits for slot 3 are withheld.
+            if i != 3 {
+                let j = c.slot_info.j;
+                let precommit = aggregate_commit(
+                    CommitType::Precommit,
+                    i,
+                    i,
+                    &crate::precommit_message(&[], j, i),
+                    2,
+                );
+                assert!(c.process(precommit).await.aggregated);
+            }
+        }
+
+        assert_eq!(c.unfinalized_slots(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn aggregate_rebroadcast_on_redelivery() {
+        time::pause();
+
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = signed_commit(
+            CommitType::Precommit,
+            i,
+            j,
+            &crate::precommit_message(&[], j, i),
+            1,
+        );
+
+        // The signed precommit fills the channel, so the aggregate is deferred and eventually
+        // dropped.
+        assert!(c.process(precommit.clone()).await.aggregated);
+        assert_eq!(c.metrics().results_deferred, 1);
+        time::sleep(RESULT_RETRY_TIMEOUT * 2).await;
+
+        assert!(!result_rx.recv().await.unwrap().aggregated);
+        assert!(result_rx.try_recv().is_err());
+
+        let outcome = c.process(precommit.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::AlreadyAggregated));
+        let aggregate = result_rx.recv().await.unwrap();
+        assert!(aggregate.aggregated);
+        assert_eq!(Some(aggregate), c.slot_info.aggregate);
+
+        // Re-broadcasts are rate limited.
+        c.process(precommit.clone()).await;
+        assert!(result_rx.try_recv().is_err());
+
+        time::advance(AGGREGATE_REBROADCAST_INTERVAL).await;
+        c.process(precommit).await;
+        assert!(result_rx.recv().await.unwrap().aggregated);
+    }
+
+    #[tokio::test]
+    async fn min_aggregate_interval() {
+        time::pause();
+
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let config = BounceConfig {
+            num_cubesats: 3,
+            min_aggregate_interval_ms: 500,
+            ..Default::default()
+        };
+
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let precommit1 = signed_commit(CommitType::Precommit, i, j, &msg, 1);
+        let precommit2 = signed_commit(CommitType::Precommit, i, j, &msg, 2);
+        let precommit3 = signed_commit(CommitType::Precommit, i, j, &msg, 3);
+
+        assert!(c.process(precommit1).await.signed);
+        assert!(!result_rx.recv().await.unwrap().aggregated);
+        assert!(c.process(precommit2).await.aggregated);
+        assert!(result_rx.recv().await.unwrap().aggregated);
+
+        // A precommit which would have completed the aggregate right after doesn't trigger a
+        // second broadcast.
+        c.process(precommit3.clone()).await;
+        assert!(result_rx.try_recv().is_err());
+
+        time::advance(Duration::from_millis(500)).await;
+        c.process(precommit3).await;
+        assert!(result_rx.recv().await.unwrap().aggregated);
+    }
+
+    #[tokio::test]
+    async fn metrics_handle_read_from_another_task() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
+
+        let metrics = c.metrics_handle();
+        let reader = tokio::spawn(async move {
+            while metrics.snapshot().commits_received < 2 {
+                tokio::task::yield_now().await;
+            }
+            metrics.snapshot()
+        });
+
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        for signer_id in 1..=2 {
+            c.process(signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+                .await;
+            tokio::task::yield_now().await;
+        }
+
+        let observed = reader.await.unwrap();
+        assert_eq!(observed.commits_received, 2);
+        assert_eq!(observed.signatures, 1);
+        assert_eq!(observed, c.metrics());
+    }
+
+    #[tokio::test]
+    async fn unreachable_quorum_noncommit_in_phase2() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, timer_rx, command_rx);
+        let mut registry = PeerRegistry::new();
+        for id in 0..3 {
+            registry.insert(id, vec![id as u8]);
+        }
+        c.set_registry(registry);
+        assert!(c.quorum_reachable());
+
+        // With two of three units faulty, the supermajority of 2 can't be reached.
+        let registry = c.registry_mut().unwrap();
+        registry.mark_faulty(1);
+        registry.mark_faulty(2);
+        assert!(!c.quorum_reachable());
+
+        tokio::spawn(async move { c.run().await });
 
-        c.process(noncommit).await;
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        assert!(result_rx.try_recv().is_err());
 
-        assert!(!c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
+        timer_tx.send(Phase::Second).unwrap();
+        tokio::task::yield_now().await;
+        let noncommit = result_rx.try_recv().unwrap();
+        assert_eq!(noncommit.typ(), CommitType::Noncommit);
+        assert_eq!(noncommit.i, 1);
     }
 
     #[tokio::test]
-    async fn phase2_commit_noncommit() {
-        // Phase 2, first send commit, then noncommit. Then the Bounce unit should sign the commit
-        // and broadcast. If it receives the noncommit right after, then bounce unit should not sign
-        // the noncommit and it only needs to keep track that it has received a noncommit.
-
-        let (result_tx, mut result_rx) = mpsc::channel(1);
+    async fn unreachable_quorum_downshift() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
-        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
 
-        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx);
+        let config = BounceConfig {
+            num_cubesats: 10,
+            downshift_on_unreachable_quorum: true,
+            ..Default::default()
+        };
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        c.set_event_sender(events_tx);
+        // Only 4 of the 10 Bounce units are reachable, while supermajority is 7.
+        let mut registry = PeerRegistry::new();
+        for id in 0..10 {
+            registry.insert(id, vec![id as u8]);
+        }
+        for id in 4..10 {
+            registry.mark_faulty(id);
+        }
+        c.set_registry(registry);
+        tokio::spawn(async move { c.run().await });
 
-        c.slot_info.phase = Phase::Second;
+        for i in 1..=2 {
+            // Every slot is reported and noncommitted as soon as it starts.
+            timer_tx.send(Phase::First).unwrap();
+            tokio::task::yield_now().await;
+            assert_eq!(
+                events_rx.try_recv().unwrap(),
+                Event::QuorumUnreachable {
+                    i,
+                    reachable: 4,
+                    needed: 7
+                }
+            );
+            let noncommit = result_rx.try_recv().unwrap();
+            assert_eq!(noncommit.typ(), CommitType::Noncommit);
+            assert_eq!(noncommit.i, i);
 
-        assert!(!c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
-        assert!(c.slot_info.precommits.is_empty());
+            // Nothing more is signed for the slot.
+            timer_tx.send(Phase::Second).unwrap();
+            timer_tx.send(Phase::Third).unwrap();
+            tokio::task::yield_now().await;
+            assert!(result_rx.try_recv().is_err());
+        }
+    }
 
-        let msg = "hello".as_bytes().to_vec();
+    #[tokio::test]
+    async fn commit_log_round_trip() {
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "bounce-commit-log-{}-{}",
+            std::process::id(),
+            thread_rng().gen::<u64>()
+        ));
 
-        let precommit = Commit {
-            typ: CommitType::Precommit.into(),
-            i: 0,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
+        let mut c = Cubesat::new(0, 5, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_commit_log(CommitLog::open(&path).unwrap());
+        c.slot_info.next();
 
-        c.process(precommit).await;
-        assert!(c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
-        assert_eq!(c.slot_info.precommits.len(), 1);
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let mut commits: Vec<Commit> = (1..=2)
+            .map(|signer_id| signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+            .collect();
+        // Dropped commits are logged too.
+        commits.push(Commit::default());
 
-        let result_opt = result_rx.recv().await;
-        assert!(result_opt.is_some());
-        let commit = result_opt.unwrap();
-        assert_eq!(commit.typ(), CommitType::Precommit);
-        assert_eq!(commit.i, 0);
-        assert_eq!(commit.msg, msg);
-        assert_eq!(commit.public_key, c.public_key);
+        for commit in commits.iter() {
+            c.process(commit.clone()).await;
+        }
 
-        let cubesat2_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat2_public_key = Bn256.derive_public_key(&cubesat2_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat2_private_key, &msg).unwrap();
+        let logged = CommitLog::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(logged, commits);
+    }
 
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: 1,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat2_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
+    #[tokio::test]
+    async fn replay_log_reproduces_aggregate() {
+        let path = std::env::temp_dir().join(format!(
+            "bounce-replay-log-{}-{}",
+            std::process::id(),
+            thread_rng().gen::<u64>()
+        ));
+        let config = BounceConfig {
+            num_cubesats: 4,
+            ..Default::default()
+        };
+        let new_cubesat = || {
+            let (result_tx, _result_rx) = mpsc::channel(5);
+            let (_request_tx, request_rx) = mpsc::channel(15);
+            let (_timer_tx, _timer_rx) = broadcast::channel(15);
+            let (_command_tx, command_rx) = mpsc::channel(1);
+            Cubesat::deterministic(
+                0,
+                b"replay",
+                config.clone(),
+                result_tx,
+                request_rx,
+                _timer_rx,
+                command_rx,
+            )
         };
 
-        c.process(noncommit).await;
-        assert_eq!(1, c.slot_info.noncommits.len());
+        // Record a session.
+        let mut c = new_cubesat();
+        c.set_commit_log(CommitLog::open(&path).unwrap());
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        let commits = (1..=3)
+            .map(|signer_id| signed_commit(CommitType::Precommit, i, j, &msg, signer_id))
+            .collect();
+        let recorded = drive_to_aggregate(&mut c, commits).await;
+
+        // Replay it into a fresh Bounce unit with the same key.
+        let mut replayed = new_cubesat();
+        replayed.enter_phase(Phase::First).await;
+        let outcomes = crate::replay_log(&path, &mut replayed).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(outcomes.last().unwrap().aggregated);
+        assert_eq!(replayed.slot_info.aggregate, Some(recorded));
     }
 
     #[tokio::test]
-    async fn phase2_noncommit_commit() {
-        // Similar as above, it only signs the first noncommit, and not the commit. Only keep track
-        // of the commit.
-
-        let (result_tx, mut result_rx) = mpsc::channel(1);
+    async fn expected_aggregate_matches_emitted() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx);
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
 
-        c.slot_info.phase = Phase::Second;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
+        c.process(signed_commit(CommitType::Precommit, i, j, &msg, 1))
+            .await;
+        let signed = result_rx.recv().await.unwrap();
 
-        assert!(!c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
-        assert!(c.slot_info.precommits.is_empty());
+        let peer = signed_commit(CommitType::Precommit, i, j, &msg, 2);
+        assert!(c.process(peer.clone()).await.aggregated);
+        let aggregate = result_rx.recv().await.unwrap();
 
-        let msg = "hello".as_bytes().to_vec();
+        let (signature, public_key) = expected_aggregate(&[signed, peer]).unwrap();
+        assert_eq!(aggregate.signature, signature);
+        assert_eq!(aggregate.public_key, public_key);
+    }
 
-        let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+    // Runs a Bounce unit which signs a precommit in phase 1, and returns what it sends once
+    // phase 3 starts.
+    async fn phase3_after_precommit(policy: Phase3Policy) -> Option<Commit> {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (request_tx, request_rx) = mpsc::channel(15);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: 1,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
+        let mut config = BounceConfig {
+            num_cubesats: 3,
+            ..Default::default()
         };
+        config.set_phase3_policy(policy);
+        let mut c = Cubesat::with_config(0, config, result_tx, request_rx, timer_rx, command_rx);
+        tokio::spawn(async move { c.run().await });
 
-        c.process(noncommit).await;
-        assert!(c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
-        assert_eq!(c.slot_info.noncommits.len(), 1);
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
 
-        let result_opt = result_rx.recv().await;
-        assert!(result_opt.is_some());
-        let commit = result_opt.unwrap();
-        assert_eq!(commit.typ(), CommitType::Noncommit);
-        assert_eq!(commit.i, 0);
-        assert_eq!(commit.msg, msg);
-        assert_eq!(commit.public_key, c.public_key);
+        let msg = crate::precommit_message(&[], 0, 1);
+        request_tx
+            .send(signed_commit(CommitType::Precommit, 1, 0, &msg, 1))
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        let precommit = result_rx.recv().await.unwrap();
+        assert_eq!(precommit.typ(), CommitType::Precommit);
 
-        let cubesat2_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat2_public_key = Bn256.derive_public_key(&cubesat2_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat2_private_key, &msg).unwrap();
+        timer_tx.send(Phase::Second).unwrap();
+        timer_tx.send(Phase::Third).unwrap();
+        tokio::task::yield_now().await;
+        result_rx.try_recv().ok()
+    }
 
-        let precommit = Commit {
-            typ: CommitType::Precommit.into(),
-            i: 1,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat2_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
+    #[tokio::test]
+    async fn phase3_policy() {
+        assert_eq!(
+            phase3_after_precommit(Phase3Policy::AbstainIfPrecommitted).await,
+            None
+        );
 
-        c.process(precommit).await;
-        assert_eq!(1, c.slot_info.noncommits.len());
+        let noncommit = phase3_after_precommit(Phase3Policy::AlwaysNoncommit)
+            .await
+            .unwrap();
+        assert_eq!(noncommit.typ(), CommitType::Noncommit);
     }
 
     #[tokio::test]
-    async fn phase2_commit_aggregate() {
-        // Tests that in phase 2 the bounce unit aggregates signatures.
-        let (result_tx, _result_rx) = mpsc::channel(5);
+    async fn empty_message_dropped() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx);
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
 
-        c.slot_info.phase = Phase::Second;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let precommit = signed_commit(CommitType::Precommit, i, j, &[], 1);
 
+        let outcome = c.process(precommit.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::EmptyMessage));
         assert!(!c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
         assert!(c.slot_info.precommits.is_empty());
+        assert!(result_rx.try_recv().is_err());
 
-        let msg = "hello".as_bytes().to_vec();
-
-        let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
-
-        let precommit = Commit {
-            typ: CommitType::Precommit.into(),
-            i: 1,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
-
-        c.process(precommit).await;
-        assert!(c.slot_info.signed);
-        assert!(c.slot_info.aggregated);
-        assert_eq!(c.slot_info.precommits.len(), 1);
+        // Unless explicitly allowed.
+        c.config.allow_empty_message = true;
+        let outcome = c.process(precommit).await;
+        assert!(outcome.signed);
     }
 
     #[tokio::test]
-    async fn phase2_noncommit_aggregate() {
-        // Tests that in phase 2 the bounce unit aggregates signatures.
+    async fn undersized_aggregate_rejected() {
         let (result_tx, _result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 1, result_tx, request_rx, _timer_rx);
+        // Supermajority of 4 is 3.
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
 
-        c.slot_info.phase = Phase::Second;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
 
-        assert!(!c.slot_info.signed);
+        let outcome = c
+            .process(aggregate_commit(CommitType::Precommit, i, i, &msg, 2))
+            .await;
+        assert_eq!(outcome.dropped, Some(DropReason::InsufficientSigners));
         assert!(!c.slot_info.aggregated);
-        assert!(c.slot_info.noncommits.is_empty());
 
-        let msg = "hello".as_bytes().to_vec();
+        let outcome = c
+            .process(aggregate_commit(CommitType::Precommit, i, i, &msg, 3))
+            .await;
+        assert!(outcome.aggregated);
+    }
 
-        let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+    #[tokio::test]
+    async fn aggregate_of_other_slot_dropped() {
+        let (result_tx, mut result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: 1,
-            j: 0,
-            msg: msg.clone(),
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.enter_phase(Phase::First).await;
+        c.enter_phase(Phase::First).await;
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
 
-        c.process(noncommit).await;
-        assert!(c.slot_info.signed);
-        assert!(c.slot_info.aggregated);
-        assert_eq!(c.slot_info.noncommits.len(), 1);
+        // A valid aggregate of only 2 signers for the previous slot, in phase 1.
+        let msg = crate::precommit_message(&[], j, i - 1);
+        let outcome = c
+            .process(aggregate_commit(
+                CommitType::Precommit,
+                i - 1,
+                i - 1,
+                &msg,
+                2,
+            ))
+            .await;
+        assert_eq!(outcome.dropped, Some(DropReason::SlotMismatch));
+        assert!(!outcome.signed);
+        assert!(!c.slot_info.aggregated);
+        assert!(c.slot_info.precommits.is_empty());
+        assert!(result_rx.try_recv().is_err());
     }
 
     #[tokio::test]
-    async fn phase3_receives_precommit() {
+    async fn forged_aggregate_rejected() {
         let (result_tx, _result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx);
-        // Assume that this Bounce unit has entered into the third phase, and signed a noncommit.
-        c.slot_info.phase = Phase::Third;
-        let msg = format!("noncommit({}, {})", c.slot_info.j + 1, c.slot_info.i);
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: c.slot_info.i,
-            j: c.slot_info.j,
-            msg: msg.clone().into_bytes(),
-            public_key: c.public_key.clone(),
-            signature: Bn256.sign(&c.private_key, &msg.as_bytes()).unwrap(),
-            aggregated: false,
-            signer_id: 0,
-        };
-
-        c.sign_and_broadcast(noncommit.clone()).await;
-        c.slot_info.noncommits.push(noncommit);
+        let mut c = Cubesat::new(0, 4, result_tx, request_rx, _timer_rx, command_rx);
+        c.slot_info.next();
 
-        let msg = "hello".as_bytes().to_vec();
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let msg = crate::precommit_message(&[], j, i);
 
-        // Then another Bounce unit sends it precommit, and the Bounce unit just keeps track of it.
+        // A commit of a single signer passed off as an aggregate of 3.
         let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg).unwrap();
+        let private_keys: Vec<Vec<u8>> = (0..4)
+            .map(|_| (0..32).map(|_| rng.gen()).collect())
+            .collect();
+        let commits: Vec<Commit> = (1..4)
+            .map(|id| Commit {
+                typ: CommitType::Precommit.into(),
+                i,
+                j,
+                msg: msg.clone(),
+                public_key: Bn256.derive_public_key(&private_keys[id]).unwrap(),
+                signature: Bn256.sign(&private_keys[id], &msg).unwrap(),
+                signer_id: id as u32,
+                ..Default::default()
+            })
+            .collect();
+        let mut forged = commits[0].clone();
+        forged.j = i;
+        forged.aggregated = true;
+        forged.signer_count = 3;
 
-        let precommit = Commit {
-            typ: CommitType::Precommit.into(),
-            i: c.slot_info.i,
-            j: c.slot_info.j,
-            msg,
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
+        // The signer ids it carries must account for the claimed count.
+        forged.signers = vec![1];
+        let outcome = c.process(forged.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::InsufficientSigners));
 
-        c.process(precommit).await;
+        // With a registry, the public key must be the aggregate of the signers' registered keys.
+        let mut registry = PeerRegistry::new();
+        for (id, private_key) in private_keys.iter().enumerate() {
+            registry.insert(id as u32, Bn256.derive_public_key(private_key).unwrap());
+        }
+        c.set_registry(registry);
 
+        forged.signers = vec![1, 2, 3];
+        let outcome = c.process(forged.clone()).await;
+        assert_eq!(outcome.dropped, Some(DropReason::InvalidSignature));
         assert!(!c.slot_info.aggregated);
-        assert_eq!(c.slot_info.precommits.len(), 1);
-        assert_eq!(c.slot_info.noncommits.len(), 1);
+
+        let (signature, public_key) = Cubesat::aggregate(&commits).unwrap();
+        let outcome = c
+            .process(Commit {
+                signature,
+                public_key,
+                ..forged
+            })
+            .await;
+        assert!(outcome.aggregated);
     }
 
     #[tokio::test]
-    async fn phase3_sign_noncommit_aggregate() {
+    async fn msg_validator_rejects_commits() {
         let (result_tx, _result_rx) = mpsc::channel(5);
         let (_request_tx, request_rx) = mpsc::channel(15);
         let (_timer_tx, _timer_rx) = broadcast::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
 
-        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx);
-
-        // Assume that this Bounce unit has entered into the third phase, and signed a noncommit.
-        c.slot_info.phase = Phase::Third;
-        let msg = format!("noncommit({}, {})", c.slot_info.j + 1, c.slot_info.i);
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: c.slot_info.i,
-            j: c.slot_info.j,
-            msg: msg.clone().into_bytes(),
-            public_key: c.public_key.clone(),
-            signature: Bn256.sign(&c.private_key, &msg.as_bytes()).unwrap(),
-            aggregated: false,
-            signer_id: 0,
-        };
-
-        c.sign_and_broadcast(noncommit.clone()).await;
-        c.slot_info.noncommits.push(noncommit);
-
-        assert!(c.slot_info.signed);
-        assert!(!c.slot_info.aggregated);
-        assert_eq!(c.slot_info.noncommits.len(), 1);
-
-        // Then another Bounce unit sends it noncommit, which results in aggregation.
-        let mut rng = thread_rng();
-        let cubesat1_private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        let cubesat1_public_key = Bn256.derive_public_key(&cubesat1_private_key).unwrap();
-        let signature = Bn256.sign(&cubesat1_private_key, &msg.as_bytes()).unwrap();
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, _timer_rx, command_rx);
+        c.set_msg_validator(Box::new(|msg: &[u8]| msg.first() == Some(&0xb0)));
+        c.slot_info.next();
 
-        let noncommit = Commit {
-            typ: CommitType::Noncommit.into(),
-            i: c.slot_info.i,
-            j: c.slot_info.j,
-            msg: msg.into_bytes(),
-            public_key: cubesat1_public_key,
-            signature,
-            aggregated: false,
-            signer_id: 0,
-        };
+        let (i, j) = (c.slot_info.i, c.slot_info.j);
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, b"hello", 1))
+            .await;
+        assert_eq!(outcome.dropped, Some(DropReason::InvalidMessage));
+        assert!(!c.slot_info.signed);
 
-        c.process(noncommit).await;
-        assert!(c.slot_info.signed);
-        assert!(c.slot_info.aggregated);
-        assert_eq!(c.slot_info.noncommits.len(), 2);
+        let outcome = c
+            .process(signed_commit(CommitType::Precommit, i, j, &[0xb0, 1, 2], 2))
+            .await;
+        assert_eq!(outcome.dropped, None);
+        assert!(outcome.signed);
     }
 }
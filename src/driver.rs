@@ -0,0 +1,272 @@
+use crate::{time_until_next_phase_with, BounceConfig, Phase, PhaseSchedule};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+
+// Capacity of the channel broadcasting phase transitions to Bounce units.
+const TIMER_CHANNEL_CAPACITY: usize = 15;
+
+/// Source of the phase transitions which move Bounce units through slots. Each Bounce unit takes
+/// the receiver returned by `subscribe` as its timer.
+pub trait SlotDriver {
+    fn subscribe(&self) -> broadcast::Receiver<Phase>;
+}
+
+/// Driver following the wall-clock schedule of a `BounceConfig`. This is the driver of Bounce
+/// units deployed in a constellation.
+pub struct TimerDriver {
+    config: BounceConfig,
+    // Durations of the phases of each slot, the fixed ones of the config if None.
+    schedule: Option<PhaseSchedule>,
+    timer_tx: broadcast::Sender<Phase>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl TimerDriver {
+    pub fn new(config: BounceConfig) -> Self {
+        TimerDriver {
+            config,
+            schedule: None,
+            timer_tx: broadcast::channel(TIMER_CHANNEL_CAPACITY).0,
+            task: None,
+        }
+    }
+
+    /// Follows `schedule` for the durations of phase 1 and phase 2 of each slot instead of the
+    /// fixed `phase1_duration` and `phase2_duration` of the config. Only applies if set before
+    /// `start`.
+    pub fn set_schedule(&mut self, schedule: PhaseSchedule) {
+        self.schedule = Some(schedule);
+    }
+
+    /// Starts phase 1 of the first slot right away, then follows the schedule on a new task.
+    /// Bounce units should subscribe beforehand, so that they don't miss the first slot.
+    pub fn start(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+
+        let config = self.config.clone();
+        let schedule = self.schedule.clone();
+        let timer_tx = self.timer_tx.clone();
+        self.task = Some(tokio::spawn(async move {
+            let start = Instant::now();
+
+            // There may be no Bounce unit subscribed.
+            let _ = timer_tx.send(Phase::First);
+            let mut current = Phase::First;
+            loop {
+                let (phase, wait) =
+                    time_until_next_phase_with(&config, schedule.as_ref(), start, Instant::now());
+                sleep(wait).await;
+                // Phases the schedule leaves empty start and end at once, but Bounce units still
+                // go through them, as they only move forward one phase at a time.
+                while current != phase {
+                    current.advance();
+                    if config.enabled_phases().contains(&current) {
+                        let _ = timer_tx.send(current.clone());
+                    }
+                }
+            }
+        }));
+    }
+}
+
+impl SlotDriver for TimerDriver {
+    fn subscribe(&self) -> broadcast::Receiver<Phase> {
+        self.timer_tx.subscribe()
+    }
+}
+
+impl Drop for TimerDriver {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+
+/// Driver advanced by explicit calls rather than by a clock, for deterministic simulations and
+/// for chains whose slots are driven by events.
+pub struct ManualDriver {
+    timer_tx: broadcast::Sender<Phase>,
+    // Index of the current slot, 0 before the first one.
+    slot: u32,
+    phase: Phase,
+}
+
+impl ManualDriver {
+    pub fn new() -> Self {
+        ManualDriver {
+            timer_tx: broadcast::channel(TIMER_CHANNEL_CAPACITY).0,
+            slot: 0,
+            phase: Phase::Stop,
+        }
+    }
+
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    pub fn phase(&self) -> &Phase {
+        &self.phase
+    }
+
+    /// Moves subscribed Bounce units to the next phase, starting the next slot after phase 3.
+    pub fn advance_phase(&mut self) {
+        self.phase.advance();
+        if self.phase == Phase::First {
+            self.slot += 1;
+        }
+        // There may be no Bounce unit subscribed.
+        let _ = self.timer_tx.send(self.phase.clone());
+    }
+
+    /// Moves subscribed Bounce units through the remaining phases of the current slot, up to
+    /// phase 1 of the next one.
+    pub fn advance_slot(&mut self) {
+        self.advance_phase();
+        while self.phase != Phase::First {
+            self.advance_phase();
+        }
+    }
+}
+
+impl Default for ManualDriver {
+    fn default() -> Self {
+        ManualDriver::new()
+    }
+}
+
+impl SlotDriver for ManualDriver {
+    fn subscribe(&self) -> broadcast::Receiver<Phase> {
+        self.timer_tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Cubesat};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time;
+
+    #[tokio::test]
+    async fn timer_driver_follows_schedule() {
+        time::pause();
+        let config = BounceConfig {
+            num_cubesats: 1,
+            slot_duration: 10,
+            phase1_duration: 2,
+            phase2_duration: 3,
+            ..Default::default()
+        };
+        let mut driver = TimerDriver::new(config);
+        // Phase 1 is twice as long in even slots.
+        driver.set_schedule(Arc::new(|i| {
+            let phase1 = Duration::from_secs(if i % 2 == 0 { 4 } else { 2 });
+            (phase1, Duration::from_secs(3))
+        }));
+        let mut timer_rx = driver.subscribe();
+        let start = Instant::now();
+        driver.start();
+
+        let mut transitions = Vec::new();
+        for _ in 0..7 {
+            let phase = timer_rx.recv().await.unwrap();
+            transitions.push((phase, start.elapsed().as_secs()));
+        }
+        assert_eq!(
+            transitions,
+            vec![
+                (Phase::First, 0),
+                (Phase::Second, 2),
+                (Phase::Third, 5),
+                (Phase::First, 10),
+                (Phase::Second, 14),
+                (Phase::Third, 17),
+                (Phase::First, 20),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn timer_driver_goes_through_empty_phase() {
+        time::pause();
+        let config = BounceConfig {
+            num_cubesats: 1,
+            slot_duration: 10,
+            phase1_duration: 8,
+            phase2_duration: 8,
+            ..Default::default()
+        };
+        let mut driver = TimerDriver::new(config);
+        let mut timer_rx = driver.subscribe();
+        let start = Instant::now();
+        driver.start();
+
+        // Phase 2 is cut short at the end of the slot, leaving phase 3 empty.
+        let mut transitions = Vec::new();
+        for _ in 0..7 {
+            let phase = timer_rx.recv().await.unwrap();
+            transitions.push((phase, start.elapsed().as_secs()));
+        }
+        assert_eq!(
+            transitions,
+            vec![
+                (Phase::First, 0),
+                (Phase::Second, 8),
+                (Phase::Third, 10),
+                (Phase::First, 10),
+                (Phase::Second, 18),
+                (Phase::Third, 20),
+                (Phase::First, 20),
+            ]
+        );
+
+        // A Bounce unit following these transitions moves on to every slot.
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let mut c = Cubesat::new(0, 1, result_tx, request_rx, driver.subscribe(), command_rx);
+        for (phase, _) in transitions {
+            c.enter_phase(phase).await;
+        }
+        assert_eq!(c.slot(), 3);
+    }
+
+    #[tokio::test]
+    async fn manual_driver_advances_slots() {
+        let mut driver = ManualDriver::new();
+
+        let (result_tx, _result_rx) = mpsc::channel(5);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (command_tx, command_rx) = mpsc::channel(1);
+        let mut c = Cubesat::new(0, 3, result_tx, request_rx, driver.subscribe(), command_rx);
+        let task = tokio::spawn(async move {
+            c.run().await;
+            c
+        });
+
+        for slot in 1..=3 {
+            driver.advance_slot();
+            assert_eq!(driver.slot(), slot);
+            assert_eq!(driver.phase(), &Phase::First);
+            tokio::task::yield_now().await;
+        }
+        driver.advance_phase();
+        assert_eq!((driver.slot(), driver.phase()), (3, &Phase::Second));
+        tokio::task::yield_now().await;
+
+        command_tx.send(Command::Terminate).await.unwrap();
+        let c = task.await.unwrap();
+        assert_eq!(c.slot(), 3);
+        assert_eq!(c.phase(), &Phase::Second);
+        for i in 1..=2 {
+            let record = c.history().get(i).unwrap();
+            assert_eq!(record.phase, Phase::Third);
+        }
+    }
+}
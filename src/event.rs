@@ -0,0 +1,63 @@
+use crate::commit::CommitType;
+use crate::{Commit, Phase, PublicKey, VerifyError};
+use std::time::Duration;
+
+/// Notable occurrences in a Bounce unit, surfaced on the optional channel set by
+/// `Cubesat::set_event_sender`.
+// Events are rare, so the size of `SafetyViolation` isn't worth boxing its commits.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// An aggregate conflicting with the one already accepted for slot i was received. The
+    /// accepted aggregate is retained.
+    SafetyViolation {
+        i: u32,
+        accepted: Commit,
+        conflicting: Commit,
+    },
+    /// A verified commit of a type this Bounce unit doesn't expect in its current phase was
+    /// received, e.g. a noncommit during phase 1. This suggests the sender's clock is ahead of or
+    /// behind ours.
+    PhaseDesync {
+        i: u32,
+        phase: Phase,
+        typ: CommitType,
+        signer_id: u32,
+    },
+    /// This Bounce unit rotated to the key of a new epoch. Verifiers need the public key to check
+    /// its commits from now on.
+    EpochKey { epoch: u64, public_key: PublicKey },
+    /// A signer presented another public key than the one last learned for its id. Whether the new
+    /// key was accepted depends on `BounceConfig::key_change_policy`.
+    KeyChanged {
+        signer_id: u32,
+        previous: Vec<u8>,
+        public_key: Vec<u8>,
+        accepted: bool,
+    },
+    /// An aggregate produced by this Bounce unit failed its own verification, which points to a
+    /// bug in aggregation or an inconsistent set of collected commits. It wasn't broadcast.
+    SelfAggregateInvalid {
+        i: u32,
+        typ: CommitType,
+        error: VerifyError,
+    },
+    /// An accepted aggregate advanced the index of the last committed slot from `from` to `to`,
+    /// skipping the slots in between. This suggests this Bounce unit missed their aggregates.
+    CommitGap { from: u32, to: u32 },
+    /// The commits of one type collected for slot i reached supermajority, in the given phase and
+    /// this long after the slot started. Aggregation follows, so comparing with the time the
+    /// aggregate is emitted separates waiting for signatures from aggregating them.
+    QuorumReached {
+        i: u32,
+        phase: Phase,
+        elapsed_since_slot_start: Duration,
+    },
+    /// Slot i started with fewer Bounce units reachable, i.e. registered and not known to be
+    /// faulty, than the supermajority needed, so it can't finalize with an aggregate of precommits.
+    QuorumUnreachable {
+        i: u32,
+        reachable: usize,
+        needed: usize,
+    },
+}
@@ -0,0 +1,172 @@
+use crate::{Command, Commit, Signer, SignerError};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::SendError;
+
+/// Errors returned when a command can't be delivered to a Bounce unit.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The Bounce unit is gone, along with its command channel.
+    Closed(Command),
+    /// The Bounce unit only acts on commands signed by its controller, and the handle has none,
+    /// see `CubesatHandle::with_controller`.
+    Unsigned(Command),
+    /// The controller failed to sign the command.
+    Signer(SignerError),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Closed(command) => {
+                write!(f, "Bounce unit is gone, {:?} not delivered", command)
+            }
+            CommandError::Unsigned(command) => write!(
+                f,
+                "Bounce unit requires signed commands, {:?} not signed",
+                command
+            ),
+            CommandError::Signer(e) => write!(f, "failed to sign command: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+// Signs the commands sent through a handle on behalf of the controller.
+#[derive(Clone)]
+struct Controller {
+    domain: Vec<u8>,
+    signer: Arc<dyn Signer>,
+    // Counter of the next command, shared by the clones of the handle so that it keeps increasing.
+    counter: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for Controller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Controller")
+            .field("domain", &self.domain)
+            .field("counter", &self.counter)
+            .finish()
+    }
+}
+
+/// Clonable handle to interact with a Bounce unit spawned by `Cubesat::spawn`.
+#[derive(Clone, Debug)]
+pub struct CubesatHandle {
+    id: usize,
+    // sender to deliver commits to the Bounce unit
+    request_tx: mpsc::Sender<Commit>,
+    // sender to deliver commands to the Bounce unit
+    command_tx: mpsc::Sender<Command>,
+    // Whether the Bounce unit only acts on commands signed by its controller.
+    requires_signed_commands: bool,
+    controller: Option<Controller>,
+}
+
+impl CubesatHandle {
+    pub fn new(
+        id: usize,
+        request_tx: mpsc::Sender<Commit>,
+        command_tx: mpsc::Sender<Command>,
+    ) -> Self {
+        CubesatHandle {
+            id,
+            request_tx,
+            command_tx,
+            requires_signed_commands: false,
+            controller: None,
+        }
+    }
+
+    /// Makes the handle refuse to send unsigned commands, which a Bounce unit configured with a
+    /// `BounceConfig::controller_key` would drop.
+    pub fn requiring_signed_commands(mut self) -> Self {
+        self.requires_signed_commands = true;
+        self
+    }
+
+    /// Signs every command sent through the handle and its clones with the controller's signer,
+    /// for a Bounce unit of the constellation `domain`. Counters start from `next_counter`, which
+    /// must be greater than the one of any command the controller signed for the Bounce unit
+    /// before.
+    pub fn with_controller(
+        mut self,
+        domain: Vec<u8>,
+        signer: Arc<dyn Signer>,
+        next_counter: u64,
+    ) -> Self {
+        self.controller = Some(Controller {
+            domain,
+            signer,
+            counter: Arc::new(AtomicU64::new(next_counter)),
+        });
+        self
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Delivers a commit to the Bounce unit as if it came from the communications hub.
+    pub async fn submit(&self, commit: Commit) -> Result<(), SendError<Commit>> {
+        self.request_tx.send(commit).await
+    }
+
+    /// Delivers a command to the Bounce unit, signed by the controller if the handle has one.
+    pub async fn command(&self, command: Command) -> Result<(), CommandError> {
+        let command = match &self.controller {
+            Some(controller) => {
+                let counter = controller.counter.fetch_add(1, Ordering::SeqCst);
+                command
+                    .signed(
+                        &controller.domain,
+                        self.id as u32,
+                        counter,
+                        controller.signer.as_ref(),
+                    )
+                    .map_err(CommandError::Signer)?
+            }
+            None if self.requires_signed_commands && !matches!(command, Command::Signed { .. }) => {
+                return Err(CommandError::Unsigned(command));
+            }
+            None => command,
+        };
+        self.command_tx
+            .send(command)
+            .await
+            .map_err(|SendError(command)| CommandError::Closed(command))
+    }
+
+    /// Terminates the Bounce unit. Terminating a Bounce unit which already stopped, e.g. at the
+    /// request of another supervisor, isn't an error.
+    pub async fn terminate(&self) -> Result<(), CommandError> {
+        match self.command(Command::Terminate).await {
+            // The channel is only closed once the Bounce unit is gone.
+            Err(CommandError::Closed(_)) => Ok(()),
+            result => result,
+        }
+    }
+
+    pub async fn pause(&self) -> Result<(), CommandError> {
+        self.command(Command::Pause).await
+    }
+
+    pub async fn resume(&self) -> Result<(), CommandError> {
+        self.command(Command::Resume).await
+    }
+
+    pub async fn freeze_slot(&self) -> Result<(), CommandError> {
+        self.command(Command::FreezeSlot).await
+    }
+
+    pub async fn unfreeze_slot(&self) -> Result<(), CommandError> {
+        self.command(Command::UnfreezeSlot).await
+    }
+
+    pub async fn abort_slot(&self) -> Result<(), CommandError> {
+        self.command(Command::AbortSlot).await
+    }
+}
@@ -0,0 +1,263 @@
+use crate::commit::CommitType;
+use crate::{Commit, CubesatHandle};
+use log::info;
+use rand::Rng;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time;
+
+/// Bounce units to which the communications hub relays a signed commit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BroadcastPolicy {
+    /// Every Bounce unit but the signer.
+    #[default]
+    All,
+    /// Only Bounce units which haven't signed a commit of the same slot yet, as those which have
+    /// don't act on further commits. Aggregates and heartbeats are still relayed to every Bounce
+    /// unit.
+    NonSigners,
+}
+
+/// Network conditions simulated by the communications hub on every link to a Bounce unit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HubConfig {
+    // Delay before a relayed commit reaches a Bounce unit.
+    pub latency: Duration,
+    // Probability in [0, 1] that a relayed commit never reaches a Bounce unit.
+    pub drop_rate: f64,
+    pub broadcast_policy: BroadcastPolicy,
+}
+
+/// Relays the results of Bounce units to each other, forwarding aggregates to the ground station.
+///
+/// Each link delays or drops commits as configured by `HubConfig`. Drops are drawn from an
+/// injected RNG, so a seeded RNG makes a simulation reproducible.
+pub struct CommunicationsHub<R> {
+    config: HubConfig,
+    handles: Vec<CubesatHandle>,
+    rng: R,
+    // sender to forward aggregates to the ground station
+    aggregate_tx: mpsc::Sender<Commit>,
+    // Latest slot relayed, and the ids of the Bounce units whose signed commit for it was relayed.
+    slot: u32,
+    signers: HashSet<u32>,
+}
+
+impl<R: Rng> CommunicationsHub<R> {
+    pub fn new(
+        config: HubConfig,
+        handles: Vec<CubesatHandle>,
+        rng: R,
+        aggregate_tx: mpsc::Sender<Commit>,
+    ) -> Self {
+        CommunicationsHub {
+            config,
+            handles,
+            rng,
+            aggregate_tx,
+            slot: 0,
+            signers: HashSet::new(),
+        }
+    }
+
+    // Notes that the signer of a commit signed for its slot.
+    fn learn_signer(&mut self, commit: &Commit) {
+        if commit.i > self.slot {
+            self.slot = commit.i;
+            self.signers.clear();
+        }
+        if commit.i == self.slot {
+            self.signers.insert(commit.signer_id);
+        }
+    }
+
+    // Whether the broadcast policy leaves the Bounce unit with the given id out of the relay of a
+    // commit.
+    fn skips(&self, id: usize, commit: &Commit) -> bool {
+        match self.config.broadcast_policy {
+            BroadcastPolicy::All => false,
+            BroadcastPolicy::NonSigners => {
+                !commit.aggregated
+                    && commit.typ() != CommitType::Heartbeat
+                    && commit.i == self.slot
+                    && self.signers.contains(&(id as u32))
+            }
+        }
+    }
+
+    /// Relays a commit to every Bounce unit but its sender, or those the broadcast policy leaves
+    /// out, each after the configured latency unless the link drops it. An aggregate is echoed
+    /// back to its sender too, acknowledging it.
+    pub async fn relay(&mut self, commit: Commit) {
+        if commit.aggregated && self.aggregate_tx.send(commit.clone()).await.is_err() {
+            info!("Communications Hub\tAggregate channel closed");
+        }
+        // A heartbeat doesn't sign for its slot.
+        if !commit.aggregated && commit.typ() != CommitType::Heartbeat {
+            self.learn_signer(&commit);
+        }
+
+        let drop_rate = self.config.drop_rate.max(0.0).min(1.0);
+        for handle in self.handles.iter() {
+            if (handle.id() == commit.signer_id as usize && !commit.aggregated)
+                || self.skips(handle.id(), &commit)
+                || self.rng.gen_bool(drop_rate)
+            {
+                continue;
+            }
+
+            let handle = handle.clone();
+            let commit = commit.clone();
+            let latency = self.config.latency;
+            tokio::spawn(async move {
+                time::sleep(latency).await;
+                if handle.submit(commit).await.is_err() {
+                    info!(
+                        "Communications Hub\tFailed to send a commit to Bounce Unit {}",
+                        handle.id()
+                    );
+                }
+            });
+        }
+    }
+
+    /// Relays the results of Bounce units until the channel closes.
+    pub async fn run(&mut self, mut result_rx: mpsc::Receiver<Commit>) {
+        while let Some(commit) = result_rx.recv().await {
+            self.relay(commit).await;
+        }
+        info!("Communications Hub\tResult channel closed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{precommit_message, BounceConfig, CommitBuilder, Cubesat, LocalSigner, Phase};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tokio::sync::broadcast;
+
+    const N: u32 = 7;
+    const MAX_SLOTS: u32 = 10;
+
+    #[tokio::test]
+    async fn finalizes_with_drops() {
+        time::pause();
+
+        let config = BounceConfig {
+            num_cubesats: N,
+            slot_duration: 10,
+            phase1_duration: 4,
+            phase2_duration: 3,
+            ..Default::default()
+        };
+        let (result_tx, result_rx) = mpsc::channel(10 * (N * N) as usize);
+        let (timer_tx, _timer_rx) = broadcast::channel(15);
+        let handles: Vec<_> = (0..N as usize)
+            .map(|id| Cubesat::spawn(id, config.clone(), result_tx.clone(), timer_tx.subscribe()).0)
+            .collect();
+
+        let hub_config = HubConfig {
+            latency: Duration::from_millis(200),
+            drop_rate: 0.5,
+            ..Default::default()
+        };
+        let (aggregate_tx, mut aggregate_rx) = mpsc::channel(10 * N as usize);
+        let mut hub = CommunicationsHub::new(
+            hub_config,
+            handles.clone(),
+            StdRng::seed_from_u64(7),
+            aggregate_tx,
+        );
+        tokio::spawn(async move { hub.run(result_rx).await });
+
+        let ground_station = LocalSigner::random();
+        let mut finalized = None;
+        for i in 1..=MAX_SLOTS {
+            timer_tx.send(Phase::First).unwrap();
+            tokio::task::yield_now().await;
+
+            let msg = precommit_message(&[], i - 1, i);
+            let request = CommitBuilder::precommit(i, i - 1, msg)
+                .signer_id(100)
+                .sign_with(&ground_station)
+                .unwrap()
+                .build();
+            for handle in handles.iter() {
+                handle.submit(request.clone()).await.unwrap();
+            }
+
+            time::sleep(Duration::from_secs(config.phase1_duration as u64)).await;
+            timer_tx.send(Phase::Second).unwrap();
+            time::sleep(Duration::from_secs(config.phase2_duration as u64)).await;
+            timer_tx.send(Phase::Third).unwrap();
+            time::sleep(Duration::from_secs(
+                (config.slot_duration - config.phase1_duration - config.phase2_duration) as u64,
+            ))
+            .await;
+
+            if let Ok(aggregate) = aggregate_rx.try_recv() {
+                finalized = Some(aggregate);
+                break;
+            }
+        }
+
+        for handle in handles.iter() {
+            handle.terminate().await.unwrap();
+        }
+
+        let aggregate = finalized.expect("no slot finalized despite drops");
+        assert!(aggregate.signer_count as usize >= crate::supermajority(N as usize));
+    }
+
+    // Whether each Bounce unit received a commit since the last call, once relays are done.
+    async fn delivered(request_rxs: &mut [mpsc::Receiver<Commit>]) -> Vec<bool> {
+        time::sleep(Duration::from_millis(1)).await;
+        request_rxs
+            .iter_mut()
+            .map(|request_rx| request_rx.try_recv().is_ok())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn non_signers_policy() {
+        time::pause();
+
+        let mut request_rxs = Vec::new();
+        let mut handles = Vec::new();
+        for id in 0..3 {
+            let (request_tx, request_rx) = mpsc::channel(5);
+            let (command_tx, _command_rx) = mpsc::channel(1);
+            handles.push(CubesatHandle::new(id, request_tx, command_tx));
+            request_rxs.push(request_rx);
+        }
+        let hub_config = HubConfig {
+            broadcast_policy: BroadcastPolicy::NonSigners,
+            ..Default::default()
+        };
+        let (aggregate_tx, _aggregate_rx) = mpsc::channel(5);
+        let mut hub =
+            CommunicationsHub::new(hub_config, handles, StdRng::seed_from_u64(7), aggregate_tx);
+
+        let signed = |signer_id: u32| Commit {
+            i: 1,
+            signer_id,
+            ..Default::default()
+        };
+        hub.relay(signed(1)).await;
+        assert_eq!(delivered(&mut request_rxs).await, vec![true, false, true]);
+
+        // Bounce unit 1 signed, so the commit of 2 only goes to 0.
+        hub.relay(signed(2)).await;
+        assert_eq!(delivered(&mut request_rxs).await, vec![true, false, false]);
+
+        // Aggregates reach signers too, and are echoed back to their sender as an
+        // acknowledgement.
+        let mut aggregate = signed(0);
+        aggregate.aggregated = true;
+        hub.relay(aggregate).await;
+        assert_eq!(delivered(&mut request_rxs).await, vec![true, true, true]);
+    }
+}
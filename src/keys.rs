@@ -0,0 +1,135 @@
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use rand::{thread_rng, Rng};
+use std::fmt;
+
+/// Length in bytes of a Bn256 private key.
+pub const PRIVATE_KEY_LENGTH: usize = 32;
+
+/// Errors returned when constructing a key from bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyError {
+    /// The bytes don't have the length of a key.
+    InvalidLength { expected: usize, actual: usize },
+    /// The bytes aren't a valid compressed Bn256 public key.
+    InvalidPublicKey,
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::InvalidLength { expected, actual } => write!(
+                f,
+                "invalid key length: expected {} bytes, got {}",
+                expected, actual
+            ),
+            KeyError::InvalidPublicKey => write!(f, "invalid Bn256 public key"),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// Bn256 public key of a Bounce unit, in compressed form.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PublicKey(Vec<u8>);
+
+impl PublicKey {
+    /// Parses a compressed Bn256 public key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyError> {
+        Bn256
+            .aggregate_public_keys(&[bytes])
+            .map_err(|_| KeyError::InvalidPublicKey)?;
+        Ok(PublicKey(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PublicKey({})", hex::encode(&self.0))
+    }
+}
+
+/// Bn256 private key of a Bounce unit.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PrivateKey(Vec<u8>);
+
+impl PrivateKey {
+    /// Any 32 bytes are a valid private key, as they're reduced modulo the group order.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyError> {
+        if bytes.len() != PRIVATE_KEY_LENGTH {
+            return Err(KeyError::InvalidLength {
+                expected: PRIVATE_KEY_LENGTH,
+                actual: bytes.len(),
+            });
+        }
+        Ok(PrivateKey(bytes.to_vec()))
+    }
+
+    /// Generates a fresh private key.
+    pub fn random() -> Self {
+        let mut rng = thread_rng();
+        PrivateKey((0..PRIVATE_KEY_LENGTH).map(|_| rng.gen()).collect())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(
+            Bn256
+                .derive_public_key(&self.0)
+                .expect("private keys have a valid length"),
+        )
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the private key.
+        f.write_str("PrivateKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_key_length() {
+        assert_eq!(
+            PrivateKey::from_bytes(&[1; 31]),
+            Err(KeyError::InvalidLength {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert!(PrivateKey::from_bytes(&[1; 32]).is_ok());
+        assert_eq!(format!("{:?}", PrivateKey::random()), "PrivateKey(..)");
+    }
+
+    #[test]
+    fn public_key_from_bytes() {
+        let public_key = PrivateKey::random().public_key();
+        assert_eq!(PublicKey::from_bytes(public_key.as_bytes()), Ok(public_key));
+        assert_eq!(
+            PublicKey::from_bytes(&[1, 2, 3]),
+            Err(KeyError::InvalidPublicKey)
+        );
+    }
+}
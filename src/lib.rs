@@ -3,13 +3,85 @@ use log4rs::append::console::ConsoleAppender;
 use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Config, Root};
 
+pub use aggregator::*;
+pub mod aggregator;
+pub use command::*;
+pub mod command;
+pub use commit_builder::*;
+pub mod commit_builder;
+pub use commit_log::*;
+pub mod commit_log;
+pub use config::*;
+pub mod config;
 pub use cubesat::*;
 pub mod cubesat;
+pub use driver::*;
+pub mod driver;
+pub use event::*;
+pub mod event;
+pub use handle::*;
+pub mod handle;
+pub use hub::*;
+pub mod hub;
+pub use keys::*;
+pub mod keys;
+pub use message::*;
+pub mod message;
+pub use metrics::*;
+pub mod metrics;
+pub use points::*;
+pub mod points;
+pub use pool::*;
+pub mod pool;
+pub use registry::*;
+pub mod registry;
+pub use schedule::*;
+pub mod schedule;
+pub use signature_cache::*;
+pub mod signature_cache;
+pub use signer::*;
+pub mod signer;
+pub use slot_history::*;
+pub mod slot_history;
 pub use slot_info::*;
 pub mod slot_info;
+#[cfg(feature = "http-status")]
+pub use status::*;
+#[cfg(feature = "http-status")]
+pub mod status;
+pub use transcript::*;
+pub mod transcript;
+pub use verify::*;
+pub mod verify;
 
 tonic::include_proto!("bounce"); // The string specified here must match the proto package name
 
+impl commit::CommitType {
+    /// Every commit type, in the order of their protobuf values.
+    pub const ALL: [commit::CommitType; 3] = [
+        commit::CommitType::Precommit,
+        commit::CommitType::Noncommit,
+        commit::CommitType::Heartbeat,
+    ];
+}
+
+/// Latest version of the signature scheme, which Bounce units sign with. Commits signed with any
+/// version up to it are accepted.
+pub const SIGNATURE_SCHEME_VERSION: u32 = 0;
+
+impl Commit {
+    /// Whether the commit was signed with a version of the signature scheme this build supports.
+    pub fn version_supported(&self) -> bool {
+        (0..=SIGNATURE_SCHEME_VERSION).contains(&self.version)
+    }
+
+    /// The type of the commit, or None if its protobuf value matches no `CommitType`, e.g. a type
+    /// added by a newer version of the protocol. `typ()` reads such values as `Precommit`.
+    pub fn checked_typ(&self) -> Option<commit::CommitType> {
+        commit::CommitType::from_i32(self.typ)
+    }
+}
+
 pub fn supermajority(n: usize) -> usize {
     (n as f64 / 3.0 * 2.0).ceil() as usize
 }
@@ -50,4 +122,37 @@ mod tests {
         assert_eq!(supermajority(1), 1);
         assert_eq!(supermajority(3), 2);
     }
+
+    #[test]
+    fn commit_type_round_trip() {
+        use commit::CommitType;
+
+        for &typ in CommitType::ALL.iter() {
+            // Adding a variant breaks this match, as a reminder to add it to `ALL` too.
+            match typ {
+                CommitType::Precommit | CommitType::Noncommit | CommitType::Heartbeat => {}
+            }
+
+            let commit = Commit {
+                typ: typ.into(),
+                ..Default::default()
+            };
+            assert_eq!(commit.typ(), typ);
+            assert_eq!(commit.checked_typ(), Some(typ));
+            assert_eq!(CommitType::from_i32(typ as i32), Some(typ));
+
+            let aggregate = Commit {
+                typ: typ.into(),
+                aggregated: true,
+                ..Default::default()
+            };
+            assert_eq!(aggregate.checked_typ(), Some(typ));
+        }
+
+        let unknown = Commit {
+            typ: CommitType::ALL.len() as i32,
+            ..Default::default()
+        };
+        assert_eq!(unknown.checked_typ(), None);
+    }
 }
@@ -0,0 +1,230 @@
+//! Canonical messages signed by Bounce units.
+//!
+//! Every message is prefixed with the constellation `domain`, so a signature obtained in one
+//! constellation never verifies in another one running the same slot numbers. An empty domain
+//! leaves the message as is.
+
+use crate::commit::CommitType;
+use crate::Commit;
+use sha2::{Digest, Sha256};
+
+fn with_domain(domain: &[u8], msg: String) -> Vec<u8> {
+    if domain.is_empty() {
+        return msg.into_bytes();
+    }
+
+    let mut bytes = Vec::with_capacity(domain.len() + 1 + msg.len());
+    bytes.extend_from_slice(domain);
+    bytes.push(b':');
+    bytes.extend_from_slice(msg.as_bytes());
+    bytes
+}
+
+fn slot_message(domain: &[u8], name: &str, j: u32, i: u32, payload: &[u8]) -> Vec<u8> {
+    if payload.is_empty() {
+        return with_domain(domain, format!("{}({}, {})", name, j + 1, i));
+    }
+
+    with_domain(
+        domain,
+        format!("{}({}, {}, {})", name, j + 1, i, hex::encode(payload)),
+    )
+}
+
+/// Message for a precommit of slots (j+1, i), where j is the last committed slot.
+pub fn precommit_message(domain: &[u8], j: u32, i: u32) -> Vec<u8> {
+    precommit_message_with_payload(domain, j, i, &[])
+}
+
+/// Message for a noncommit of slots (j+1, i), where j is the last committed slot.
+pub fn noncommit_message(domain: &[u8], j: u32, i: u32) -> Vec<u8> {
+    noncommit_message_with_payload(domain, j, i, &[])
+}
+
+/// Message for a precommit of slots (j+1, i) committing to external data, rendered as
+/// `precommit(j+1, i, hex(payload))`. An empty payload gives the same message as
+/// `precommit_message`.
+pub fn precommit_message_with_payload(domain: &[u8], j: u32, i: u32, payload: &[u8]) -> Vec<u8> {
+    slot_message(domain, "precommit", j, i, payload)
+}
+
+/// Message for a noncommit of slots (j+1, i) committing to external data, rendered as
+/// `noncommit(j+1, i, hex(payload))`.
+pub fn noncommit_message_with_payload(domain: &[u8], j: u32, i: u32, payload: &[u8]) -> Vec<u8> {
+    slot_message(domain, "noncommit", j, i, payload)
+}
+
+/// Folds the digest of a justification, the encoded aggregate of the last committed slot, into a
+/// message, rendered as `msg; justified by hex(sha256(justification))`. An empty justification
+/// leaves the message as is.
+pub fn with_justification(mut msg: Vec<u8>, justification: &[u8]) -> Vec<u8> {
+    if justification.is_empty() {
+        return msg;
+    }
+
+    msg.extend_from_slice(b"; justified by ");
+    msg.extend_from_slice(hex::encode(Sha256::digest(justification)).as_bytes());
+    msg
+}
+
+/// Message for a heartbeat of a Bounce unit in slot i, rendered as `heartbeat(i)`.
+pub fn heartbeat_message(domain: &[u8], i: u32) -> Vec<u8> {
+    with_domain(domain, format!("heartbeat({})", i))
+}
+
+/// Message a controller signs to authenticate a command to Bounce unit `target`, rendered as
+/// `command(name, target, counter)`. The controller increases the counter with every command it
+/// signs, so that a signed command can neither be replayed nor redirected to another Bounce unit.
+pub fn command_message(domain: &[u8], name: &str, target: u32, counter: u64) -> Vec<u8> {
+    with_domain(
+        domain,
+        format!("command({}, {}, {})", name, target, counter),
+    )
+}
+
+/// Whether the message has the digest of the justification folded in by `with_justification`.
+pub fn is_justified_by(msg: &[u8], justification: &[u8]) -> bool {
+    let suffix = with_justification(Vec::new(), justification);
+    msg.len() > suffix.len() && msg.ends_with(&suffix)
+}
+
+/// Whether a commit signs the canonical message of its type for slots (j+1, i): the precommit or
+/// noncommit message carrying its payload, with its justification folded in. Heartbeats never sign
+/// a slot message.
+pub fn is_canonical_message(domain: &[u8], commit: &Commit) -> bool {
+    // Slot j+1 doesn't exist.
+    if commit.j == u32::MAX {
+        return false;
+    }
+
+    let msg = match commit.typ() {
+        CommitType::Precommit => {
+            precommit_message_with_payload(domain, commit.j, commit.i, &commit.payload)
+        }
+        CommitType::Noncommit => {
+            noncommit_message_with_payload(domain, commit.j, commit.i, &commit.payload)
+        }
+        CommitType::Heartbeat => return false,
+    };
+    with_justification(msg, &commit.justification) == commit.msg
+}
+
+/// Whether a message is a precommit or noncommit message of the domain, as opposed to a free-form
+/// message, e.g. one of a ground station.
+pub fn is_slot_message(domain: &[u8], msg: &[u8]) -> bool {
+    ["precommit", "noncommit"]
+        .iter()
+        .any(|name| msg.starts_with(&with_domain(domain, format!("{}(", name))))
+}
+
+/// Whether the message was produced for the given domain.
+pub fn in_domain(domain: &[u8], msg: &[u8]) -> bool {
+    if domain.is_empty() {
+        return true;
+    }
+
+    msg.len() > domain.len() && msg.starts_with(domain) && msg[domain.len()] == b':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_message_test() {
+        let domain = b"constellation-a";
+        let commit = |typ: CommitType, j, i, msg: Vec<u8>| Commit {
+            typ: typ.into(),
+            i,
+            j,
+            msg,
+            ..Default::default()
+        };
+
+        let msg = precommit_message(domain, 2, 5);
+        assert!(is_canonical_message(
+            domain,
+            &commit(CommitType::Precommit, 2, 5, msg.clone())
+        ));
+        for (typ, j, i) in [
+            (CommitType::Precommit, 2, 3),
+            (CommitType::Precommit, 1, 5),
+            (CommitType::Noncommit, 2, 5),
+            (CommitType::Heartbeat, 2, 5),
+            // Slot 5 isn't slot 50.
+            (CommitType::Precommit, 2, 50),
+        ]
+        .iter()
+        {
+            assert!(!is_canonical_message(
+                domain,
+                &commit(*typ, *j, *i, msg.clone())
+            ));
+        }
+
+        // Anything past the canonical message, e.g. a payload the commit doesn't carry, makes it
+        // another message.
+        let mut extended = msg.clone();
+        extended.extend_from_slice(b" and more");
+        assert!(!is_canonical_message(
+            domain,
+            &commit(CommitType::Precommit, 2, 5, extended)
+        ));
+
+        let mut noncommit = commit(
+            CommitType::Noncommit,
+            2,
+            5,
+            with_justification(noncommit_message_with_payload(domain, 2, 5, b"ab"), b"x"),
+        );
+        assert!(!is_canonical_message(domain, &noncommit));
+        noncommit.payload = b"ab".to_vec();
+        noncommit.justification = b"x".to_vec();
+        assert!(is_canonical_message(domain, &noncommit));
+
+        assert!(is_slot_message(domain, &msg));
+        assert!(!is_slot_message(domain, b"hello"));
+        assert!(!is_canonical_message(
+            domain,
+            &commit(CommitType::Precommit, 2, 5, b"hello".to_vec())
+        ));
+    }
+
+    #[test]
+    fn message_domain_test() {
+        assert_eq!(precommit_message(&[], 0, 1), b"precommit(1, 1)".to_vec());
+        assert_eq!(noncommit_message(&[], 2, 5), b"noncommit(3, 5)".to_vec());
+
+        let msg = precommit_message(b"A", 0, 1);
+        assert_eq!(msg, b"A:precommit(1, 1)".to_vec());
+        assert!(in_domain(b"A", &msg));
+        assert!(!in_domain(b"B", &msg));
+        assert!(!in_domain(b"AB", &msg));
+        assert!(in_domain(&[], &msg));
+
+        assert_eq!(
+            precommit_message_with_payload(b"A", 0, 1, &[0xab, 0x01]),
+            b"A:precommit(1, 1, ab01)".to_vec()
+        );
+        assert_eq!(
+            noncommit_message_with_payload(&[], 2, 5, &[]),
+            noncommit_message(&[], 2, 5)
+        );
+        assert_eq!(heartbeat_message(&[], 4), b"heartbeat(4)".to_vec());
+        assert_eq!(
+            command_message(b"A", "terminate", 2, 7),
+            b"A:command(terminate, 2, 7)".to_vec()
+        );
+    }
+
+    #[test]
+    fn message_justification() {
+        let msg = precommit_message(&[], 1, 2);
+        assert_eq!(with_justification(msg.clone(), &[]), msg);
+
+        let justified = with_justification(msg, b"aggregate of slot 1");
+        assert!(justified.starts_with(b"precommit(2, 2); justified by "));
+        assert!(is_justified_by(&justified, b"aggregate of slot 1"));
+        assert!(!is_justified_by(&justified, b"aggregate of slot 0"));
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counters describing the activity of a single Bounce unit.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CubesatMetrics {
+    // Number of commits received from the communications hub.
+    pub commits_received: u64,
+    // Number of received commits dropped without being counted.
+    pub commits_dropped: u64,
+    // Number of precommits or noncommits signed by this unit.
+    pub signatures: u64,
+    // Number of aggregate signatures produced by this unit.
+    pub aggregations: u64,
+    // Number of results that couldn't be sent right away because the channel to the
+    // communications hub was full.
+    pub results_deferred: u64,
+    // Number of received commits set aside because the slot processing budget was exhausted.
+    pub commits_deferred: u64,
+}
+
+impl CubesatMetrics {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Renders the counters in the Prometheus text exposition format, labelled with the id of the
+    /// Bounce unit.
+    pub fn to_prometheus(&self, id: usize) -> String {
+        let counters = [
+            (
+                "bounce_commits_received_total",
+                "Commits received from the communications hub.",
+                self.commits_received,
+            ),
+            (
+                "bounce_commits_dropped_total",
+                "Received commits dropped without being counted.",
+                self.commits_dropped,
+            ),
+            (
+                "bounce_signatures_total",
+                "Precommits or noncommits signed.",
+                self.signatures,
+            ),
+            (
+                "bounce_aggregations_total",
+                "Aggregate signatures produced.",
+                self.aggregations,
+            ),
+            (
+                "bounce_results_deferred_total",
+                "Results deferred because the channel to the communications hub was full.",
+                self.results_deferred,
+            ),
+            (
+                "bounce_commits_deferred_total",
+                "Commits set aside because the slot processing budget was exhausted.",
+                self.commits_deferred,
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in counters.iter() {
+            writeln!(out, "# HELP {} {}", name, help).unwrap();
+            writeln!(out, "# TYPE {} counter", name).unwrap();
+            writeln!(out, "{}{{id=\"{}\"}} {}", name, id, value).unwrap();
+        }
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    commits_received: AtomicU64,
+    commits_dropped: AtomicU64,
+    signatures: AtomicU64,
+    aggregations: AtomicU64,
+    results_deferred: AtomicU64,
+    commits_deferred: AtomicU64,
+}
+
+/// Shared counters of a Bounce unit. Clones are cheap and read the same counters, so monitoring
+/// code on another task can observe them while the unit is running.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsHandle {
+    counters: Arc<Counters>,
+}
+
+impl MetricsHandle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Current values of the counters.
+    pub fn snapshot(&self) -> CubesatMetrics {
+        let c = &self.counters;
+        CubesatMetrics {
+            commits_received: c.commits_received.load(Ordering::Relaxed),
+            commits_dropped: c.commits_dropped.load(Ordering::Relaxed),
+            signatures: c.signatures.load(Ordering::Relaxed),
+            aggregations: c.aggregations.load(Ordering::Relaxed),
+            results_deferred: c.results_deferred.load(Ordering::Relaxed),
+            commits_deferred: c.commits_deferred.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn to_prometheus(&self, id: usize) -> String {
+        self.snapshot().to_prometheus(id)
+    }
+
+    pub(crate) fn inc_commits_received(&self) {
+        self.counters
+            .commits_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_commits_dropped(&self) {
+        self.counters
+            .commits_dropped
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_signatures(&self) {
+        self.counters.signatures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_aggregations(&self) {
+        self.counters.aggregations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_results_deferred(&self) {
+        self.counters
+            .results_deferred
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_commits_deferred(&self) {
+        self.counters
+            .commits_deferred
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_prometheus_test() {
+        let metrics = CubesatMetrics {
+            commits_received: 3,
+            commits_dropped: 1,
+            signatures: 1,
+            aggregations: 0,
+            results_deferred: 2,
+            commits_deferred: 4,
+        };
+
+        let text = metrics.to_prometheus(2);
+        assert!(text.contains("# TYPE bounce_commits_received_total counter\n"));
+        assert!(text.contains("bounce_commits_received_total{id=\"2\"} 3\n"));
+        assert!(text.contains("bounce_commits_dropped_total{id=\"2\"} 1\n"));
+        assert!(text.contains("bounce_signatures_total{id=\"2\"} 1\n"));
+        assert!(text.contains("bounce_aggregations_total{id=\"2\"} 0\n"));
+        assert!(text.contains("bounce_results_deferred_total{id=\"2\"} 2\n"));
+        assert!(text.contains("bounce_commits_deferred_total{id=\"2\"} 4\n"));
+    }
+
+    #[test]
+    fn metrics_handle_shared() {
+        let metrics = MetricsHandle::new();
+        let writer = metrics.clone();
+
+        std::thread::spawn(move || {
+            writer.inc_commits_received();
+            writer.inc_commits_received();
+            writer.inc_aggregations();
+        })
+        .join()
+        .unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.commits_received, 2);
+        assert_eq!(snapshot.aggregations, 1);
+        assert_eq!(snapshot.signatures, 0);
+    }
+}
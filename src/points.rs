@@ -0,0 +1,144 @@
+use crate::Commit;
+use bls_signatures_rs::bn256::PublicKey as G2PublicKey;
+use bn::{AffineG1, Fq, G1};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Length in bytes of a compressed G1 point, i.e. of a signature as produced by `Bn256::sign`.
+pub const COMPRESSED_SIGNATURE_LENGTH: usize = 33;
+/// Length in bytes of an uncompressed G1 point, its x and y coordinates.
+pub const UNCOMPRESSED_SIGNATURE_LENGTH: usize = 64;
+/// Length in bytes of a compressed G2 point, i.e. of a public key as derived by `Bn256`.
+pub const COMPRESSED_PUBLIC_KEY_LENGTH: usize = 65;
+/// Length in bytes of an uncompressed G2 point, its x and y coordinates over Fq2.
+pub const UNCOMPRESSED_PUBLIC_KEY_LENGTH: usize = 128;
+
+/// Errors returned when converting the points carried by a commit between encodings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PointError {
+    /// The signature isn't a valid G1 point in either encoding.
+    InvalidSignature,
+    /// The public key isn't a valid G2 point in either encoding.
+    InvalidPublicKey,
+}
+
+impl fmt::Display for PointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointError::InvalidSignature => write!(f, "invalid signature point"),
+            PointError::InvalidPublicKey => write!(f, "invalid public key point"),
+        }
+    }
+}
+
+impl std::error::Error for PointError {}
+
+/// The commit with its signature and public key in compressed form, the one `Bn256` verifies.
+/// Points which already are compressed are kept as they are, so only an aggregate emitted with
+/// uncompressed points is copied.
+pub fn with_compressed_points(commit: &Commit) -> Result<Cow<'_, Commit>, PointError> {
+    if commit.signature.len() != UNCOMPRESSED_SIGNATURE_LENGTH
+        && commit.public_key.len() != UNCOMPRESSED_PUBLIC_KEY_LENGTH
+    {
+        return Ok(Cow::Borrowed(commit));
+    }
+
+    let mut compressed = commit.clone();
+    if commit.signature.len() == UNCOMPRESSED_SIGNATURE_LENGTH {
+        compressed.signature = compress_signature(&commit.signature)?;
+    }
+    if commit.public_key.len() == UNCOMPRESSED_PUBLIC_KEY_LENGTH {
+        compressed.public_key = G2PublicKey::from_uncompressed(&commit.public_key)
+            .and_then(|public_key| public_key.to_compressed())
+            .map_err(|_| PointError::InvalidPublicKey)?;
+    }
+    Ok(Cow::Owned(compressed))
+}
+
+/// The commit with its signature and public key in uncompressed form, which verifiers convert
+/// back with `with_compressed_points`.
+pub fn with_uncompressed_points(commit: &Commit) -> Result<Commit, PointError> {
+    let mut uncompressed = commit.clone();
+    if commit.signature.len() == COMPRESSED_SIGNATURE_LENGTH {
+        uncompressed.signature = decompress_signature(&commit.signature)?;
+    }
+    if commit.public_key.len() == COMPRESSED_PUBLIC_KEY_LENGTH {
+        uncompressed.public_key = G2PublicKey::from_compressed(&commit.public_key)
+            .and_then(|public_key| public_key.to_uncompressed())
+            .map_err(|_| PointError::InvalidPublicKey)?;
+    }
+    Ok(uncompressed)
+}
+
+// Encodes a G1 point as the parity of y, 0x02 if even and 0x03 if odd, followed by x, as
+// `Bn256::sign` does.
+fn compress_signature(bytes: &[u8]) -> Result<Vec<u8>, PointError> {
+    let x = Fq::from_slice(&bytes[..32]).map_err(|_| PointError::InvalidSignature)?;
+    let y = Fq::from_slice(&bytes[32..]).map_err(|_| PointError::InvalidSignature)?;
+    // Checks that the point is on the curve.
+    AffineG1::new(x, y).map_err(|_| PointError::InvalidSignature)?;
+
+    let odd = y
+        .into_u256()
+        .get_bit(0)
+        .ok_or(PointError::InvalidSignature)?;
+    let mut compressed = vec![0; COMPRESSED_SIGNATURE_LENGTH];
+    compressed[0] = if odd { 3 } else { 2 };
+    x.into_u256()
+        .to_big_endian(&mut compressed[1..])
+        .map_err(|_| PointError::InvalidSignature)?;
+    Ok(compressed)
+}
+
+fn decompress_signature(bytes: &[u8]) -> Result<Vec<u8>, PointError> {
+    let point = G1::from_compressed(bytes)
+        .ok()
+        .and_then(AffineG1::from_jacobian)
+        .ok_or(PointError::InvalidSignature)?;
+
+    let mut uncompressed = vec![0; UNCOMPRESSED_SIGNATURE_LENGTH];
+    let (x, y) = uncompressed.split_at_mut(32);
+    point
+        .x()
+        .into_u256()
+        .to_big_endian(x)
+        .and_then(|_| point.y().into_u256().to_big_endian(y))
+        .map_err(|_| PointError::InvalidSignature)?;
+    Ok(uncompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommitBuilder, LocalSigner};
+
+    #[test]
+    fn points_round_trip() {
+        let commit = CommitBuilder::precommit(1, 0, b"msg".to_vec())
+            .sign_with(&LocalSigner::random())
+            .unwrap()
+            .build();
+
+        let uncompressed = with_uncompressed_points(&commit).unwrap();
+        assert_eq!(uncompressed.signature.len(), UNCOMPRESSED_SIGNATURE_LENGTH);
+        assert_eq!(
+            uncompressed.public_key.len(),
+            UNCOMPRESSED_PUBLIC_KEY_LENGTH
+        );
+        assert_eq!(
+            with_compressed_points(&uncompressed).unwrap().into_owned(),
+            commit
+        );
+        assert!(matches!(
+            with_compressed_points(&commit),
+            Ok(Cow::Borrowed(_))
+        ));
+
+        let mut invalid = uncompressed;
+        invalid.signature[63] ^= 1;
+        assert_eq!(
+            with_compressed_points(&invalid),
+            Err(PointError::InvalidSignature)
+        );
+    }
+}
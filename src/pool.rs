@@ -0,0 +1,367 @@
+use crate::{BounceConfig, Commit, Cubesat, Phase, RunOutcome};
+use log::info;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{self, Instant};
+
+/// Bounce units driven cooperatively on a single task, sharing one set of phase transitions.
+///
+/// Simulating a large constellation with one task per `Cubesat` is heavy, so the pool instead
+/// hands every commit to each of its units in turn, rotating which unit goes first.
+pub struct CubesatPool {
+    cubesats: Vec<Cubesat>,
+    // Index of the unit which receives the next commit first.
+    next: usize,
+}
+
+impl CubesatPool {
+    /// Creates `config.num_cubesats` Bounce units with ids starting from 0, all sending their
+    /// results to `result_tx`.
+    pub fn new(config: BounceConfig, result_tx: mpsc::Sender<Commit>) -> Self {
+        let cubesats = (0..config.num_cubesats as usize)
+            .map(|id| {
+                // Units of a pool never run their own loop, so their input channels stay unused.
+                let (_request_tx, request_rx) = mpsc::channel(1);
+                let (_timer_tx, timer_rx) = broadcast::channel(1);
+                let (_command_tx, command_rx) = mpsc::channel(1);
+                Cubesat::with_config(
+                    id,
+                    config.clone(),
+                    result_tx.clone(),
+                    request_rx,
+                    timer_rx,
+                    command_rx,
+                )
+            })
+            .collect();
+
+        CubesatPool { cubesats, next: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cubesats.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cubesats.is_empty()
+    }
+
+    pub fn cubesats(&self) -> &[Cubesat] {
+        &self.cubesats
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Cubesat> {
+        self.cubesats.get(id)
+    }
+
+    /// Moves every unit of the pool to the given phase.
+    pub async fn enter_phase(&mut self, phase: Phase) {
+        for cubesat in self.cubesats.iter_mut() {
+            cubesat.enter_phase(phase.clone()).await;
+        }
+    }
+
+    /// Hands a commit to every unit of the pool. Units which exhausted their slot processing budget
+    /// set it aside for `run` to process once nothing else is pending.
+    pub async fn deliver(&mut self, commit: Commit) {
+        let n = self.cubesats.len();
+        for k in 0..n {
+            let id = (self.next + k) % n;
+            self.cubesats[id].receive(commit.clone()).await;
+        }
+
+        if n > 0 {
+            self.next = (self.next + 1) % n;
+        }
+    }
+
+    // Broadcasts the unacknowledged aggregates of units again, once their retry is due.
+    async fn retry_aggregates_if_due(&mut self) {
+        for cubesat in self.cubesats.iter_mut() {
+            cubesat.retry_aggregate_if_due().await;
+        }
+    }
+
+    // Has every unit of the pool broadcast a heartbeat.
+    async fn send_heartbeats(&mut self) {
+        for cubesat in self.cubesats.iter_mut() {
+            cubesat.send_heartbeat().await;
+        }
+    }
+
+    // Processes one commit set aside by each unit whose slot processing budget was exhausted.
+    async fn process_deferred(&mut self) {
+        for cubesat in self.cubesats.iter_mut() {
+            cubesat.process_deferred().await;
+        }
+    }
+
+    // Signs the phase 3 noncommits which were delayed to tolerate clock skew, once they're due.
+    async fn sign_noncommits_if_due(&mut self) {
+        for cubesat in self.cubesats.iter_mut() {
+            cubesat.sign_noncommit_if_due().await;
+        }
+    }
+
+    /// Drives the pool with commits from the communications hub and phase transitions from the
+    /// timer, until either channel closes. Like `Cubesat::run`, it also signs delayed noncommits,
+    /// retries unacknowledged aggregates and broadcasts heartbeats when they're due.
+    pub async fn run(
+        &mut self,
+        mut request_rx: mpsc::Receiver<Commit>,
+        mut timer_rx: broadcast::Receiver<Phase>,
+    ) -> RunOutcome {
+        // Units of a pool share their config, hence their heartbeat interval.
+        let heartbeat_interval = self.cubesats.first().and_then(|c| c.heartbeat_interval());
+        // The period is only used if heartbeats are enabled, but must be positive.
+        let period = heartbeat_interval.unwrap_or(Duration::from_secs(1));
+        let mut heartbeat = time::interval_at(Instant::now() + period, period);
+        heartbeat.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        loop {
+            let noncommit_at = self.cubesats.iter().filter_map(|c| c.noncommit_at()).min();
+            let retry_at = self
+                .cubesats
+                .iter()
+                .filter_map(|c| c.aggregate_retry_at())
+                .min();
+            let deferred = self.cubesats.iter().any(|c| c.has_deferred());
+
+            tokio::select! {
+                phase = timer_rx.recv() => {
+                    match phase {
+                        Ok(phase) => self.enter_phase(phase).await,
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return RunOutcome::InputClosed,
+                    }
+                }
+                _ = heartbeat.tick(), if heartbeat_interval.is_some() => {
+                    self.send_heartbeats().await;
+                }
+                _ = time::sleep_until(noncommit_at.unwrap_or_else(Instant::now)), if noncommit_at.is_some() => {
+                    self.sign_noncommits_if_due().await;
+                }
+                _ = time::sleep_until(retry_at.unwrap_or_else(Instant::now)), if retry_at.is_some() => {
+                    self.retry_aggregates_if_due().await;
+                }
+                commit = request_rx.recv() => {
+                    match commit {
+                        Some(commit) => self.deliver(commit).await,
+                        None => {
+                            info!("Cubesat Pool\tRequest channel closed");
+                            return RunOutcome::InputClosed;
+                        }
+                    }
+                }
+                // As in `Cubesat::run`, commits set aside are only processed once nothing else is
+                // pending.
+                _ = tokio::task::yield_now(), if deferred => {
+                    self.process_deferred().await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::CommitType;
+    use crate::{supermajority, verify_aggregate, LocalSigner};
+
+    const N: u32 = 50;
+
+    fn ground_station_precommit() -> Commit {
+        let signer = LocalSigner::random();
+        let msg = crate::precommit_message(&[], 0, 1);
+        crate::CommitBuilder::precommit(1, 0, msg)
+            .signer_id(100)
+            .sign_with(&signer)
+            .unwrap()
+            .build()
+    }
+
+    fn assert_finalizes(aggregate: &Commit, ground_station: &Commit) {
+        assert!(aggregate.aggregated);
+        assert_eq!(aggregate.typ(), CommitType::Precommit);
+        assert_eq!((aggregate.i, aggregate.j), (1, 1));
+        assert_eq!(aggregate.msg, ground_station.msg);
+        assert_eq!(
+            verify_aggregate(aggregate, supermajority(N as usize)),
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn pool_finalizes_like_tasks() {
+        let config = BounceConfig {
+            num_cubesats: N,
+            ..Default::default()
+        };
+        let ground_station = ground_station_precommit();
+
+        // Pool, with this test relaying results back like the communications hub.
+        let (result_tx, mut result_rx) = mpsc::channel(10 * (N * N) as usize);
+        let mut pool = CubesatPool::new(config.clone(), result_tx);
+        assert_eq!(pool.len(), N as usize);
+
+        pool.enter_phase(Phase::First).await;
+        pool.deliver(ground_station.clone()).await;
+
+        let mut pool_aggregate = None;
+        while let Ok(commit) = result_rx.try_recv() {
+            if commit.aggregated && pool_aggregate.is_none() {
+                pool_aggregate = Some(commit.clone());
+            }
+            pool.deliver(commit).await;
+        }
+
+        let pool_aggregate = pool_aggregate.unwrap();
+        assert_finalizes(&pool_aggregate, &ground_station);
+        for cubesat in pool.cubesats() {
+            let record = cubesat.history().get(1).unwrap();
+            assert!(record.finalized);
+            assert_eq!(record.outcome, Some(CommitType::Precommit));
+        }
+
+        // One task per Bounce unit.
+        let (result_tx, mut result_rx) = mpsc::channel(10 * (N * N) as usize);
+        let (timer_tx, _timer_rx) = broadcast::channel(15);
+        let handles: Vec<_> = (0..N as usize)
+            .map(|id| Cubesat::spawn(id, config.clone(), result_tx.clone(), timer_tx.subscribe()).0)
+            .collect();
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        for handle in handles.iter() {
+            handle.submit(ground_station.clone()).await.unwrap();
+        }
+
+        let task_aggregate = loop {
+            let commit = result_rx.recv().await.unwrap();
+            if commit.aggregated {
+                break commit;
+            }
+            for handle in handles.iter() {
+                handle.submit(commit.clone()).await.unwrap();
+            }
+        };
+        for handle in handles.iter() {
+            handle.terminate().await.unwrap();
+        }
+
+        assert_finalizes(&task_aggregate, &ground_station);
+        assert_eq!(task_aggregate.signer_count, pool_aggregate.signer_count);
+    }
+
+    #[tokio::test]
+    async fn pool_drains_deferred_commits() {
+        // The budget is exhausted by the first commit a unit verifies, so that further commits
+        // are set aside.
+        let config = BounceConfig {
+            num_cubesats: 4,
+            slot_cpu_budget_ms: 1,
+            ..Default::default()
+        };
+        let ground_station = ground_station_precommit();
+
+        let (result_tx, mut result_rx) = mpsc::channel(100);
+        let (request_tx, request_rx) = mpsc::channel(100);
+        let (timer_tx, timer_rx) = broadcast::channel(15);
+        let mut pool = CubesatPool::new(config, result_tx);
+        let run = tokio::spawn(async move {
+            let outcome = pool.run(request_rx, timer_rx).await;
+            (pool, outcome)
+        });
+
+        timer_tx.send(Phase::First).unwrap();
+        tokio::task::yield_now().await;
+        request_tx.send(ground_station.clone()).await.unwrap();
+
+        let aggregate = time::timeout(std::time::Duration::from_secs(10), async {
+            loop {
+                let commit = result_rx.recv().await.unwrap();
+                if commit.aggregated {
+                    break commit;
+                }
+                request_tx.send(commit).await.unwrap();
+            }
+        })
+        .await
+        .expect("deferred commits were never processed");
+        assert_eq!(aggregate.msg, ground_station.msg);
+
+        drop(request_tx);
+        let (pool, outcome) = run.await.unwrap();
+        assert_eq!(outcome, RunOutcome::InputClosed);
+        assert!(pool
+            .cubesats()
+            .iter()
+            .any(|c| c.metrics().commits_deferred > 0));
+    }
+
+    #[tokio::test]
+    async fn pool_sends_heartbeats() {
+        time::pause();
+
+        let config = BounceConfig {
+            num_cubesats: 3,
+            heartbeat_interval_ms: 1000,
+            ..Default::default()
+        };
+        let (result_tx, mut result_rx) = mpsc::channel(15);
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, timer_rx) = broadcast::channel(15);
+        let mut pool = CubesatPool::new(config, result_tx);
+        tokio::spawn(async move { pool.run(request_rx, timer_rx).await });
+
+        time::sleep(Duration::from_millis(1500)).await;
+        let mut signers = Vec::new();
+        while let Ok(commit) = result_rx.try_recv() {
+            assert_eq!(commit.typ(), CommitType::Heartbeat);
+            signers.push(commit.signer_id);
+        }
+        signers.sort_unstable();
+        assert_eq!(signers, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn pool_retries_aggregates() {
+        time::pause();
+
+        let config = BounceConfig {
+            num_cubesats: 4,
+            aggregate_max_retries: 2,
+            ..Default::default()
+        };
+        let ground_station = ground_station_precommit();
+
+        // Relays results back like a communications hub which loses every aggregate.
+        let (result_tx, mut result_rx) = mpsc::channel(100);
+        let mut pool = CubesatPool::new(config, result_tx);
+        pool.enter_phase(Phase::First).await;
+        pool.deliver(ground_station).await;
+        let mut aggregates = 0;
+        while let Ok(commit) = result_rx.try_recv() {
+            if commit.aggregated {
+                aggregates += 1;
+            } else {
+                pool.deliver(commit).await;
+            }
+        }
+        assert!(aggregates > 0);
+
+        let (_request_tx, request_rx) = mpsc::channel(15);
+        let (_timer_tx, timer_rx) = broadcast::channel(15);
+        tokio::spawn(async move { pool.run(request_rx, timer_rx).await });
+
+        // Every aggregate is broadcast again twice, one second apart, then given up on.
+        time::sleep(Duration::from_secs(10)).await;
+        let mut retries = 0;
+        while let Ok(commit) = result_rx.try_recv() {
+            assert!(commit.aggregated);
+            retries += 1;
+        }
+        assert_eq!(retries, 2 * aggregates);
+    }
+}
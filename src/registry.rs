@@ -0,0 +1,229 @@
+use crate::{Commit, PeerEntry, PeerList};
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use prost::Message;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Errors returned when importing a `PeerRegistry`.
+#[derive(Debug)]
+pub enum RegistryError {
+    Decode(prost::DecodeError),
+    /// The public key registered for a Bounce unit isn't a valid Bn256 public key.
+    InvalidKey {
+        id: u32,
+    },
+    /// A faulty Bounce unit isn't registered.
+    UnknownFaulty {
+        id: u32,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Decode(e) => write!(f, "failed to decode registry: {}", e),
+            RegistryError::InvalidKey { id } => {
+                write!(f, "invalid public key for Bounce unit {}", id)
+            }
+            RegistryError::UnknownFaulty { id } => {
+                write!(f, "faulty Bounce unit {} is not registered", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl From<prost::DecodeError> for RegistryError {
+    fn from(e: prost::DecodeError) -> Self {
+        RegistryError::Decode(e)
+    }
+}
+
+/// Known Bounce units of a constellation, mapping their ids to their public keys.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerRegistry {
+    peers: BTreeMap<u32, Vec<u8>>,
+    // Registered Bounce units known to be faulty, which aren't expected to sign.
+    faulty: BTreeSet<u32>,
+}
+
+impl PeerRegistry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the public key of a Bounce unit, returning the key previously registered for the
+    /// same id if any.
+    pub fn insert(&mut self, id: u32, public_key: Vec<u8>) -> Option<Vec<u8>> {
+        self.peers.insert(id, public_key)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&[u8]> {
+        self.peers.get(&id).map(|k| k.as_slice())
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.peers.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Marks a registered Bounce unit as faulty. Returns false if the id isn't registered.
+    pub fn mark_faulty(&mut self, id: u32) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        self.faulty.insert(id);
+        true
+    }
+
+    pub fn clear_faulty(&mut self, id: u32) {
+        self.faulty.remove(&id);
+    }
+
+    pub fn is_faulty(&self, id: u32) -> bool {
+        self.faulty.contains(&id)
+    }
+
+    /// Number of registered Bounce units which aren't known to be faulty.
+    pub fn num_reachable(&self) -> usize {
+        self.peers.len() - self.faulty.len()
+    }
+
+    /// Ids of registered Bounce units in increasing order.
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.peers.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        self.peers.iter().map(|(id, k)| (*id, k.as_slice()))
+    }
+
+    /// Encodes the registry as a `PeerList` protobuf message, e.g. for a controller to sign and
+    /// distribute it to the Bounce units.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let list = PeerList {
+            peers: self
+                .iter()
+                .map(|(id, public_key)| PeerEntry {
+                    id,
+                    public_key: public_key.to_vec(),
+                })
+                .collect(),
+            faulty: self.faulty.iter().copied().collect(),
+        };
+        let mut buf = Vec::with_capacity(list.encoded_len());
+        // Encoding only fails if the buffer can't grow.
+        list.encode(&mut buf).unwrap();
+        buf
+    }
+
+    /// Decodes a registry encoded by `to_bytes`, checking that every public key is a valid
+    /// Bn256 public key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RegistryError> {
+        let list = PeerList::decode(bytes)?;
+
+        let mut peers = PeerRegistry::new();
+        for peer in list.peers {
+            if Bn256.aggregate_public_keys(&[&peer.public_key]).is_err() {
+                return Err(RegistryError::InvalidKey { id: peer.id });
+            }
+            peers.insert(peer.id, peer.public_key);
+        }
+        for id in list.faulty {
+            if !peers.mark_faulty(id) {
+                return Err(RegistryError::UnknownFaulty { id });
+            }
+        }
+        Ok(peers)
+    }
+}
+
+/// Ids of the registered Bounce units which aren't among the signers of an aggregate, in
+/// increasing order.
+pub fn non_signers(registry: &PeerRegistry, aggregate: &Commit) -> Vec<usize> {
+    let signers: BTreeSet<u32> = aggregate.signers.iter().copied().collect();
+    registry
+        .ids()
+        .filter(|id| !signers.contains(id))
+        .map(|id| id as usize)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LocalSigner, Signer};
+
+    #[test]
+    fn peer_registry_test() {
+        let mut registry = PeerRegistry::new();
+        assert!(registry.is_empty());
+
+        assert_eq!(registry.insert(3, vec![3]), None);
+        assert_eq!(registry.insert(1, vec![1]), None);
+        assert_eq!(registry.insert(3, vec![4]), Some(vec![3]));
+
+        assert_eq!(registry.len(), 2);
+        assert!(registry.contains(1));
+        assert!(!registry.contains(2));
+        assert_eq!(registry.get(3), Some(&[4u8][..]));
+        assert_eq!(registry.ids().collect::<Vec<_>>(), vec![1, 3]);
+
+        assert!(!registry.mark_faulty(2));
+        assert!(registry.mark_faulty(3));
+        assert!(registry.is_faulty(3));
+        assert_eq!(registry.num_reachable(), 1);
+        registry.clear_faulty(3);
+        assert_eq!(registry.num_reachable(), 2);
+    }
+
+    #[test]
+    fn registry_round_trip() {
+        let mut registry = PeerRegistry::new();
+        for id in 0..4 {
+            registry.insert(id, LocalSigner::random().public_key().into_bytes());
+        }
+        registry.mark_faulty(2);
+
+        let imported = PeerRegistry::from_bytes(&registry.to_bytes()).unwrap();
+        assert_eq!(imported, registry);
+        assert!(imported.is_faulty(2));
+    }
+
+    #[test]
+    fn registry_with_malformed_key() {
+        let mut registry = PeerRegistry::new();
+        registry.insert(0, LocalSigner::random().public_key().into_bytes());
+        registry.insert(1, vec![1, 2, 3]);
+
+        assert!(matches!(
+            PeerRegistry::from_bytes(&registry.to_bytes()),
+            Err(RegistryError::InvalidKey { id: 1 })
+        ));
+    }
+
+    #[test]
+    fn non_signers_of_aggregate() {
+        let mut registry = PeerRegistry::new();
+        for id in 0..5 {
+            registry.insert(id, vec![id as u8]);
+        }
+        let aggregate = Commit {
+            aggregated: true,
+            signer_count: 3,
+            signers: vec![4, 0, 2],
+            ..Default::default()
+        };
+
+        assert_eq!(non_signers(&registry, &aggregate), vec![1, 3]);
+    }
+}
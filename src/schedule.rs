@@ -0,0 +1,194 @@
+use crate::{BounceConfig, Phase};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Durations of phase 1 and phase 2 of slot i, the first slot being 1, for deployments whose
+/// phases aren't the same length in every slot. Phase 3 lasts until the end of the slot, whose
+/// duration stays `BounceConfig::slot_duration`. It's shared with the task driving the slots.
+pub type PhaseSchedule = Arc<dyn Fn(u32) -> (Duration, Duration) + Send + Sync>;
+
+// Offset of each phase from the start of slot i. Phase 3 lasts until the end of the slot, and
+// phases longer than the slot are cut short.
+fn phase_offsets(
+    config: &BounceConfig,
+    schedule: Option<&PhaseSchedule>,
+    i: u32,
+) -> (Duration, Duration, Duration) {
+    let (phase1, phase2) = match schedule {
+        Some(schedule) => schedule(i),
+        None => (
+            Duration::from_secs(config.phase1_duration as u64),
+            Duration::from_secs(config.phase2_duration as u64),
+        ),
+    };
+    let slot = Duration::from_secs(config.slot_duration as u64);
+    (phase1.min(slot), (phase1 + phase2).min(slot), slot)
+}
+
+/// Number of slots fully elapsed since `start`, and the phase of the current one at `now`. Before
+/// `start`, the Bounce unit is stopped.
+pub fn phase_at(config: &BounceConfig, start: Instant, now: Instant) -> (u32, Phase) {
+    phase_at_with(config, None, start, now)
+}
+
+/// Same as `phase_at`, with the phase durations of each slot given by `schedule` if any.
+pub fn phase_at_with(
+    config: &BounceConfig,
+    schedule: Option<&PhaseSchedule>,
+    start: Instant,
+    now: Instant,
+) -> (u32, Phase) {
+    let slot = Duration::from_secs(config.slot_duration as u64);
+    if now < start || slot == Duration::from_secs(0) {
+        return (0, Phase::Stop);
+    }
+
+    let elapsed = now - start;
+    let slots = (elapsed.as_nanos() / slot.as_nanos()) as u32;
+    let offset = elapsed - slot * slots;
+    let (phase2, phase3, _) = phase_offsets(config, schedule, slots + 1);
+    let phase = if offset < phase2 {
+        Phase::First
+    } else if offset < phase3 {
+        Phase::Second
+    } else {
+        Phase::Third
+    };
+    (slots, phase)
+}
+
+/// The next phase to start after `now`, for slots starting at `start`, along with how long until
+/// it starts.
+pub fn time_until_next_phase(
+    config: &BounceConfig,
+    start: Instant,
+    now: Instant,
+) -> (Phase, Duration) {
+    time_until_next_phase_with(config, None, start, now)
+}
+
+/// Same as `time_until_next_phase`, with the phase durations of each slot given by `schedule` if
+/// any. Phases which the schedule leaves empty, e.g. phase 3 when phases 1 and 2 fill the slot,
+/// are skipped: the phase returned is the one in effect once the current phase ends.
+pub fn time_until_next_phase_with(
+    config: &BounceConfig,
+    schedule: Option<&PhaseSchedule>,
+    start: Instant,
+    now: Instant,
+) -> (Phase, Duration) {
+    let (slots, phase) = phase_at_with(config, schedule, start, now);
+    if phase == Phase::Stop {
+        return (Phase::First, start.saturating_duration_since(now));
+    }
+
+    let (phase2, phase3, slot) = phase_offsets(config, schedule, slots + 1);
+    let slot_start = start + slot * slots;
+    let next_start = match phase {
+        Phase::First => slot_start + phase2,
+        Phase::Second => slot_start + phase3,
+        _ => slot_start + slot,
+    };
+    let (_, next) = phase_at_with(config, schedule, start, next_start);
+    (next, next_start - now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_phase_at_instants() {
+        let config = BounceConfig {
+            num_cubesats: 3,
+            slot_duration: 10,
+            phase1_duration: 4,
+            phase2_duration: 3,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        let cases = [
+            (0, Phase::Second, 4_000),
+            (1_500, Phase::Second, 2_500),
+            (4_000, Phase::Third, 3_000),
+            (6_999, Phase::Third, 1),
+            (7_000, Phase::First, 3_000),
+            (9_000, Phase::First, 1_000),
+            (10_000, Phase::Second, 4_000),
+            (25_000, Phase::Third, 2_000),
+        ];
+        for (ms, phase, wait) in cases.iter() {
+            assert_eq!(
+                time_until_next_phase(&config, start, at(*ms)),
+                (phase.clone(), Duration::from_millis(*wait)),
+                "at {}ms",
+                ms
+            );
+        }
+        assert_eq!(phase_at(&config, start, at(25_000)), (2, Phase::Second));
+
+        // Before the first slot starts.
+        let later = at(2_000);
+        assert_eq!(
+            time_until_next_phase(&config, later, start),
+            (Phase::First, Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn next_phase_with_schedule() {
+        let config = BounceConfig {
+            num_cubesats: 3,
+            slot_duration: 10,
+            phase1_duration: 2,
+            phase2_duration: 3,
+            ..Default::default()
+        };
+        // Phase 1 is twice as long in even slots.
+        let schedule: PhaseSchedule = Arc::new(|i| {
+            let phase1 = Duration::from_secs(if i % 2 == 0 { 4 } else { 2 });
+            (phase1, Duration::from_secs(3))
+        });
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        let cases = [
+            (0, Phase::Second, 2_000),
+            (2_000, Phase::Third, 3_000),
+            (10_000, Phase::Second, 4_000),
+            (14_000, Phase::Third, 3_000),
+            (17_000, Phase::First, 3_000),
+            (20_000, Phase::Second, 2_000),
+        ];
+        for (ms, phase, wait) in cases.iter() {
+            assert_eq!(
+                time_until_next_phase_with(&config, Some(&schedule), start, at(*ms)),
+                (phase.clone(), Duration::from_millis(*wait)),
+                "at {}ms",
+                ms
+            );
+        }
+        assert_eq!(
+            phase_at_with(&config, Some(&schedule), start, at(13_000)),
+            (1, Phase::First)
+        );
+
+        // Phases running past the end of the slot are cut short, leaving phase 3 empty, so the
+        // next slot follows phase 2.
+        let schedule: PhaseSchedule =
+            Arc::new(|_| (Duration::from_secs(8), Duration::from_secs(8)));
+        assert_eq!(
+            time_until_next_phase_with(&config, Some(&schedule), start, at(9_000)),
+            (Phase::First, Duration::from_secs(1))
+        );
+        // Likewise phase 1 filling the slot leaves phases 2 and 3 empty.
+        let schedule: PhaseSchedule =
+            Arc::new(|_| (Duration::from_secs(12), Duration::from_secs(1)));
+        assert_eq!(
+            time_until_next_phase_with(&config, Some(&schedule), start, at(9_000)),
+            (Phase::First, Duration::from_secs(1))
+        );
+    }
+}
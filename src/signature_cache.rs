@@ -0,0 +1,131 @@
+use crate::Commit;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of verified signatures kept by default, well above the commits a slot sees.
+pub const DEFAULT_SIGNATURE_CACHE_CAPACITY: usize = 1024;
+
+// Hash of the public key, message and signature of a commit.
+type CacheKey = [u8; 32];
+
+/// Signatures verified during the current slot, so that redeliveries of a commit skip the
+/// pairing checks. Once full, the least recently inserted or refreshed signature is evicted.
+#[derive(Clone, Debug)]
+pub struct SignatureCache {
+    // Tick at which each signature was last inserted.
+    entries: HashMap<CacheKey, u64>,
+    // Signatures by the tick at which they were last inserted, oldest first.
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    capacity: usize,
+}
+
+impl Default for SignatureCache {
+    fn default() -> Self {
+        SignatureCache::with_capacity(DEFAULT_SIGNATURE_CACHE_CAPACITY)
+    }
+}
+
+impl SignatureCache {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SignatureCache {
+            entries: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+            tick: 0,
+            capacity,
+        }
+    }
+
+    /// Whether the signature of `commit` was verified. This doesn't refresh it, which `insert`
+    /// does once the commit is used.
+    pub fn contains(&self, commit: &Commit) -> bool {
+        self.entries.contains_key(&cache_key(commit))
+    }
+
+    /// Records the signature of `commit` as verified, or refreshes it if it already was.
+    pub fn insert(&mut self, commit: &Commit) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = cache_key(commit);
+        self.tick += 1;
+        if let Some(tick) = self.entries.insert(key, self.tick) {
+            self.order.remove(&tick);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.insert(self.tick, key);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn cache_key(commit: &Commit) -> CacheKey {
+    let mut hasher = Sha256::new();
+    // Length prefixes keep the boundaries between fields unambiguous.
+    for field in [&commit.public_key, &commit.msg, &commit.signature].iter() {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(msg: &[u8]) -> Commit {
+        Commit {
+            msg: msg.to_vec(),
+            public_key: vec![1; 4],
+            signature: vec![2; 4],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_inserted() {
+        let mut cache = SignatureCache::with_capacity(2);
+        cache.insert(&commit(b"a"));
+        cache.insert(&commit(b"b"));
+        // Refreshing "a" leaves "b" as the oldest.
+        cache.insert(&commit(b"a"));
+        cache.insert(&commit(b"c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(&commit(b"a")));
+        assert!(!cache.contains(&commit(b"b")));
+        assert!(cache.contains(&commit(b"c")));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn keyed_by_signature() {
+        let mut cache = SignatureCache::new();
+        cache.insert(&commit(b"a"));
+
+        let mut forged = commit(b"a");
+        forged.signature = vec![3; 4];
+        assert!(!cache.contains(&forged));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{PrivateKey, PublicKey};
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::fmt;
+
+/// Errors returned by a `Signer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignerError {
+    /// The signer is temporarily unavailable, e.g. a busy HSM. Signing may be retried.
+    Transient(String),
+    /// The signer can't produce a signature.
+    Fatal(String),
+}
+
+impl SignerError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SignerError::Transient(_))
+    }
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::Transient(e) => write!(f, "transient signing error: {}", e),
+            SignerError::Fatal(e) => write!(f, "signing error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+/// Produces the BLS signatures of a Bounce unit, so that the private key can live outside of the
+/// process, e.g. in an HSM.
+pub trait Signer: Send + Sync {
+    fn public_key(&self) -> PublicKey;
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, SignerError>;
+}
+
+/// Signer holding its private key in memory.
+#[derive(Clone)]
+pub struct LocalSigner {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+}
+
+impl LocalSigner {
+    /// Creates a signer from its private key. Only a `PrivateKey` is accepted, so a public key
+    /// can't be passed by mistake:
+    ///
+    /// ```compile_fail
+    /// use bounce::{LocalSigner, Signer};
+    ///
+    /// let public_key = LocalSigner::random().public_key();
+    /// LocalSigner::new(public_key);
+    /// ```
+    pub fn new(private_key: PrivateKey) -> Self {
+        let public_key = private_key.public_key();
+        LocalSigner {
+            private_key,
+            public_key,
+        }
+    }
+
+    /// Creates a signer with a freshly generated private key.
+    pub fn random() -> Self {
+        LocalSigner::new(PrivateKey::random())
+    }
+}
+
+impl fmt::Debug for LocalSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the private key.
+        f.debug_struct("LocalSigner")
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, SignerError> {
+        Bn256
+            .sign(self.private_key.as_bytes(), msg)
+            .map_err(|e| SignerError::Fatal(e.to_string()))
+    }
+}
+
+// Derives a 32 byte private key from a seed with HKDF-SHA256, bound to a label and an index.
+fn derive_key(seed: &[u8], label: &[u8], index: u64) -> PrivateKey {
+    let mut info = label.to_vec();
+    info.extend_from_slice(&index.to_be_bytes());
+
+    let mut key = vec![0; 32];
+    Hkdf::<Sha256>::new(None, seed)
+        .expand(&info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    PrivateKey::from_bytes(&key).expect("32 bytes is a valid private key length")
+}
+
+/// Derives the private key of an epoch from a master seed with HKDF-SHA256, so that the key of
+/// one epoch doesn't reveal those of the others.
+pub fn derive_epoch_key(master: &[u8], epoch: u64) -> PrivateKey {
+    derive_key(master, b"bounce-epoch-key", epoch)
+}
+
+/// Derives the private key of the Bounce unit with the given id from a constellation-wide seed
+/// with HKDF-SHA256, so that a whole test constellation can be reproduced from the seed.
+pub fn derive_unit_key(seed: &[u8], id: u64) -> PrivateKey {
+    derive_key(seed, b"bounce-unit-key", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_signer_sign() {
+        let signer = LocalSigner::random();
+        let msg = "hello".as_bytes();
+
+        let signature = signer.sign(msg).unwrap();
+        assert!(Bn256
+            .verify(&signature, msg, signer.public_key().as_bytes())
+            .is_ok());
+    }
+
+    #[test]
+    fn epoch_keys_differ() {
+        let master = b"master seed of a bounce unit".to_vec();
+        let msg = "hello".as_bytes();
+
+        let key0 = derive_epoch_key(&master, 0);
+        let key1 = derive_epoch_key(&master, 1);
+        assert_ne!(key0, key1);
+        assert_eq!(key0, derive_epoch_key(&master, 0));
+
+        let signer0 = LocalSigner::new(key0);
+        let signer1 = LocalSigner::new(key1);
+        assert_ne!(signer0.public_key(), signer1.public_key());
+        for signer in [signer0, signer1].iter() {
+            let signature = signer.sign(msg).unwrap();
+            assert!(Bn256
+                .verify(&signature, msg, signer.public_key().as_bytes())
+                .is_ok());
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::commit::CommitType;
+use crate::Phase;
+use std::collections::VecDeque;
+
+/// Number of slots kept by default.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 64;
+
+/// Outcome of a single slot as observed by a Bounce unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotRecord {
+    pub i: u32,
+    // The index of last committed slot when the slot was closed.
+    pub j: u32,
+    // The phase the slot was in when it finalized or was closed.
+    pub phase: Phase,
+    pub precommits: usize,
+    pub noncommits: usize,
+    pub finalized: bool,
+    // Type of the aggregate accepted for the slot, if it finalized.
+    pub outcome: Option<CommitType>,
+}
+
+/// Records of the most recent slots, oldest first. Once full, the oldest record is evicted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotHistory {
+    records: VecDeque<SlotRecord>,
+    capacity: usize,
+}
+
+impl Default for SlotHistory {
+    fn default() -> Self {
+        SlotHistory::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl SlotHistory {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SlotHistory {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records the outcome of a slot. A record for the most recent slot is replaced, so that a
+    /// slot finalizing and later closing only appears once.
+    pub fn record(&mut self, record: SlotRecord) {
+        if let Some(last) = self.records.back_mut() {
+            if last.i == record.i {
+                *last = record;
+                return;
+            }
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    pub fn get(&self, i: u32) -> Option<&SlotRecord> {
+        self.records.iter().find(|r| r.i == i)
+    }
+
+    pub fn last(&self) -> Option<&SlotRecord> {
+        self.records.back()
+    }
+
+    /// Indices of the recorded slots which never finalized, oldest first.
+    pub fn unfinalized(&self) -> Vec<u32> {
+        self.records
+            .iter()
+            .filter(|r| !r.finalized)
+            .map(|r| r.i)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SlotRecord> + '_ {
+        self.records.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(i: u32, finalized: bool) -> SlotRecord {
+        SlotRecord {
+            i,
+            j: 0,
+            phase: Phase::Second,
+            precommits: 0,
+            noncommits: 0,
+            finalized,
+            outcome: None,
+        }
+    }
+
+    #[test]
+    fn slot_history_bounded() {
+        let mut history = SlotHistory::with_capacity(2);
+        history.record(record(1, false));
+        history.record(record(2, false));
+        // Replaces the record of slot 2.
+        history.record(record(2, true));
+        history.record(record(3, false));
+
+        assert_eq!(history.len(), 2);
+        assert!(history.get(1).is_none());
+        assert!(history.get(2).unwrap().finalized);
+        assert_eq!(history.last().unwrap().i, 3);
+        assert_eq!(history.unfinalized(), vec![3]);
+    }
+}
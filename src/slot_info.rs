@@ -1,16 +1,91 @@
-use crate::Commit;
+use crate::{Commit, SignatureCache, SlotHistory, SlotRecord};
+use std::time::Duration;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum Phase {
+    #[default]
     Stop,
     First,
     Second,
     Third,
 }
 
-impl Default for Phase {
-    fn default() -> Phase {
-        Phase::Stop
+impl Phase {
+    /// The phase following this one. A slot ends after phase 3, so phase 1 of the next slot
+    /// follows it.
+    pub fn next(&self) -> Phase {
+        match self {
+            Phase::Stop => Phase::First,
+            Phase::First => Phase::Second,
+            Phase::Second => Phase::Third,
+            Phase::Third => Phase::First,
+        }
+    }
+
+    pub fn advance(&mut self) {
+        *self = self.next();
+    }
+
+    /// Whether moving from this phase to `to` is legal. A new slot may start, or the Bounce unit
+    /// stop, at any time. Otherwise phases only move forward, one at a time.
+    pub fn can_transition_to(&self, to: &Phase) -> bool {
+        self.can_transition_within(to, PhaseSet::ALL)
+    }
+
+    /// Same as `can_transition_to`, when only the given phases are enabled. Disabled phases are
+    /// skipped over.
+    pub fn can_transition_within(&self, to: &Phase, enabled: PhaseSet) -> bool {
+        if *to == Phase::First || *to == Phase::Stop {
+            return true;
+        }
+        if !enabled.contains(to) {
+            return false;
+        }
+
+        let mut next = self.next();
+        while next != *to && next != Phase::First && !enabled.contains(&next) {
+            next.advance();
+        }
+        next == *to
+    }
+}
+
+/// Set of the phases of a slot which are enabled. Phase 1 always is, as it starts a slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseSet(u32);
+
+impl PhaseSet {
+    /// Every phase, as in the full protocol.
+    pub const ALL: PhaseSet = PhaseSet(0b111);
+    /// Only phase 1, in which slots finalize by aggregating precommits or not at all.
+    pub const FIRST_ONLY: PhaseSet = PhaseSet(0b001);
+
+    /// The phases of a bit set, bit 0 for phase 1 through bit 2 for phase 3. Other bits are
+    /// ignored, and phase 1 is added.
+    pub fn from_bits(bits: u32) -> Self {
+        PhaseSet((bits & PhaseSet::ALL.0) | PhaseSet::FIRST_ONLY.0)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// This set with `phase` enabled too.
+    pub fn with(self, phase: &Phase) -> Self {
+        PhaseSet::from_bits(self.0 | PhaseSet::bit(phase))
+    }
+
+    pub fn contains(&self, phase: &Phase) -> bool {
+        self.0 & PhaseSet::bit(phase) != 0
+    }
+
+    fn bit(phase: &Phase) -> u32 {
+        match phase {
+            Phase::Stop => 0,
+            Phase::First => 0b001,
+            Phase::Second => 0b010,
+            Phase::Third => 0b100,
+        }
     }
 }
 
@@ -25,9 +100,21 @@ pub struct SlotInfo {
     pub signed: bool,
     // Whether this cubesat has aggregated signatures of at least supermajority of num_cubesats
     pub aggregated: bool,
+    // Whether the aggregation of this slot was abandoned, in which case this cubesat abstains
+    // until the next slot.
+    pub aborted: bool,
+    // The aggregate accepted for this slot, either produced by this cubesat or received.
+    pub aggregate: Option<Commit>,
     // (id, signature) of precommtis or noncommits received for this slot.
     pub precommits: Vec<Commit>,
     pub noncommits: Vec<Commit>,
+    // Signatures of commits verified during this slot.
+    pub signature_cache: SignatureCache,
+    // Time spent processing received commits during this slot, counted against
+    // `BounceConfig::slot_cpu_budget_ms`.
+    pub processing: Duration,
+    // Outcomes of recent slots.
+    pub history: SlotHistory,
 }
 
 impl SlotInfo {
@@ -35,13 +122,52 @@ impl SlotInfo {
         Default::default()
     }
 
+    /// Slot information of a Bounce unit whose first slot is `first_slot`, e.g. one joining a
+    /// running constellation. The last committed index is unknown until an aggregate is accepted.
+    pub fn starting_at(first_slot: u32) -> Self {
+        SlotInfo {
+            // The first phase 1 moves to the next slot.
+            i: first_slot.saturating_sub(1),
+            ..Default::default()
+        }
+    }
+
+    // Marks this slot as finalized with the given aggregate.
+    pub fn finalize(&mut self, aggregate: Commit) {
+        self.aggregated = true;
+        self.j = aggregate.j;
+        self.aggregate = Some(aggregate);
+        self.record();
+    }
+
     pub fn next(&mut self) {
+        // Close out the prior slot, unless this cubesat wasn't taking part in one.
+        if self.phase != Phase::Stop {
+            self.record();
+        }
+
         self.i += 1;
         self.phase = Phase::First;
         self.signed = false;
         self.aggregated = false;
+        self.aborted = false;
+        self.aggregate = None;
         self.precommits.clear();
         self.noncommits.clear();
+        self.signature_cache.clear();
+        self.processing = Duration::ZERO;
+    }
+
+    fn record(&mut self) {
+        self.history.record(SlotRecord {
+            i: self.i,
+            j: self.j,
+            phase: self.phase.clone(),
+            precommits: self.precommits.len(),
+            noncommits: self.noncommits.len(),
+            finalized: self.aggregated,
+            outcome: self.aggregate.as_ref().map(|c| c.typ()),
+        });
     }
 }
 
@@ -50,6 +176,21 @@ mod tests {
     use super::*;
     use crate::commit::CommitType;
 
+    #[test]
+    fn phase_set() {
+        assert_eq!(PhaseSet::from_bits(0), PhaseSet::FIRST_ONLY);
+        let first_and_third = PhaseSet::FIRST_ONLY.with(&Phase::Third);
+        assert!(first_and_third.contains(&Phase::Third));
+        assert!(!first_and_third.contains(&Phase::Second));
+
+        // Disabled phases are skipped over.
+        assert!(Phase::First.can_transition_within(&Phase::Third, first_and_third));
+        assert!(!Phase::First.can_transition_within(&Phase::Second, first_and_third));
+        assert!(!Phase::First.can_transition_within(&Phase::Third, PhaseSet::ALL));
+        assert!(Phase::First.can_transition_within(&Phase::First, PhaseSet::FIRST_ONLY));
+        assert!(!Phase::First.can_transition_within(&Phase::Second, PhaseSet::FIRST_ONLY));
+    }
+
     #[test]
     fn slot_info_init_test() {
         let slot_info = SlotInfo::new();
@@ -59,16 +200,41 @@ mod tests {
         assert_eq!(slot_info.phase, Phase::Stop);
         assert!(!slot_info.signed);
         assert!(!slot_info.aggregated);
+        assert!(slot_info.aggregate.is_none());
         assert!(slot_info.precommits.is_empty());
         assert!(slot_info.noncommits.is_empty());
     }
 
+    #[test]
+    fn phase_transitions() {
+        let mut phase = Phase::Stop;
+        for expected in [Phase::First, Phase::Second, Phase::Third, Phase::First].iter() {
+            assert!(phase.can_transition_to(expected));
+            phase.advance();
+            assert_eq!(&phase, expected);
+        }
+
+        assert!(!Phase::Third.can_transition_to(&Phase::Second));
+        assert!(!Phase::First.can_transition_to(&Phase::Third));
+        assert!(!Phase::Stop.can_transition_to(&Phase::Second));
+        assert!(Phase::Second.can_transition_to(&Phase::Stop));
+    }
+
+    #[test]
+    fn slot_info_starting_at() {
+        let mut slot_info = SlotInfo::starting_at(100);
+        slot_info.next();
+        assert_eq!(slot_info.i, 100);
+        assert!(slot_info.history.is_empty());
+    }
+
     #[test]
     fn slot_info_next_test() {
         let mut slot_info = SlotInfo::new();
         slot_info.phase = Phase::Second;
         slot_info.signed = true;
         slot_info.aggregated = true;
+        slot_info.aggregate = Some(Commit::default());
         slot_info.noncommits.push(Commit {
             typ: CommitType::Noncommit.into(),
             i: 0,
@@ -78,6 +244,7 @@ mod tests {
             signature: "dummy signature".to_owned().into_bytes(),
             aggregated: false,
             signer_id: 0,
+            ..Default::default()
         });
 
         slot_info.next();
@@ -85,6 +252,11 @@ mod tests {
         assert_eq!(slot_info.phase, Phase::First);
         assert!(!slot_info.signed);
         assert!(!slot_info.aggregated);
+        assert!(slot_info.aggregate.is_none());
         assert!(slot_info.noncommits.is_empty());
+
+        let record = slot_info.history.get(0).unwrap();
+        assert!(record.finalized);
+        assert_eq!(record.noncommits, 1);
     }
 }
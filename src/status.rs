@@ -0,0 +1,112 @@
+use crate::{CubesatMetrics, MetricsHandle, Phase};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Slot and phase of a Bounce unit, as reported by `GET /status`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotStatus {
+    pub slot: u32,
+    pub phase: Phase,
+}
+
+// Body of a `GET /status` response.
+#[derive(Serialize)]
+struct StatusBody {
+    slot: u32,
+    phase: String,
+    metrics: CubesatMetrics,
+}
+
+/// Binds an HTTP server to `addr` answering `GET /status` with the slot and phase returned by
+/// `snapshot_fn` and the metrics of `handle`, as JSON.
+///
+/// Returns the bound address, which differs from `addr` when binding port 0, along with the server
+/// future to run.
+pub fn serve_status<F>(
+    handle: MetricsHandle,
+    snapshot_fn: F,
+    addr: SocketAddr,
+) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error>
+where
+    F: Fn() -> SlotStatus + Send + Sync + 'static,
+{
+    let snapshot_fn = Arc::new(snapshot_fn);
+    let make_service = make_service_fn(move |_| {
+        let handle = handle.clone();
+        let snapshot_fn = snapshot_fn.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let response = respond(&req, &handle, snapshot_fn.as_ref());
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    Ok((server.local_addr(), server))
+}
+
+fn respond<F>(req: &Request<Body>, handle: &MetricsHandle, snapshot_fn: &F) -> Response<Body>
+where
+    F: Fn() -> SlotStatus,
+{
+    if req.method() != Method::GET || req.uri().path() != "/status" {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    }
+
+    let status = snapshot_fn();
+    let body = StatusBody {
+        slot: status.slot,
+        phase: format!("{:?}", status.phase),
+        metrics: handle.snapshot(),
+    };
+    let mut response = Response::new(Body::from(serde_json::to_string(&body).unwrap()));
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    #[tokio::test]
+    async fn status_reports_slot() {
+        let handle = MetricsHandle::new();
+        handle.inc_signatures();
+        let snapshot_fn = || SlotStatus {
+            slot: 42,
+            phase: Phase::Second,
+        };
+
+        let (addr, server) =
+            serve_status(handle, snapshot_fn, "127.0.0.1:0".parse().unwrap()).unwrap();
+        tokio::spawn(server);
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["slot"], 42);
+        assert_eq!(json["phase"], "Second");
+        assert_eq!(json["metrics"]["signatures"], 1);
+    }
+}
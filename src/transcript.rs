@@ -0,0 +1,204 @@
+use crate::{
+    in_domain, verify_aggregate, verify_aggregate_against, verify_signature, BounceConfig, Commit,
+    VerifyError,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Reasons a recorded slot transcript fails validation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TranscriptError {
+    /// The claimed final aggregate doesn't verify on its own.
+    InvalidAggregate(VerifyError),
+    /// The aggregate signs a message of another constellation.
+    WrongDomain,
+    /// The aggregate represents fewer signers than required.
+    InsufficientSigners { signers: usize, required: usize },
+    /// A signer of the aggregate has no valid commit of the aggregate's type, slot and message in
+    /// the transcript.
+    MissingCommit { signer_id: u32 },
+    /// A signer of the aggregate signed more than one message for the slot.
+    Equivocation { signer_id: u32 },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::InvalidAggregate(e) => write!(f, "invalid aggregate: {}", e),
+            TranscriptError::WrongDomain => {
+                write!(f, "aggregate signs a message of another domain")
+            }
+            TranscriptError::InsufficientSigners { signers, required } => write!(
+                f,
+                "aggregate represents {} signers but at least {} are required",
+                signers, required
+            ),
+            TranscriptError::MissingCommit { signer_id } => {
+                write!(
+                    f,
+                    "no valid commit of signer {} in the transcript",
+                    signer_id
+                )
+            }
+            TranscriptError::Equivocation { signer_id } => {
+                write!(f, "signer {} equivocated in the slot", signer_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+/// Validates a recorded slot offline: the claimed final `aggregate` must verify, sign a message
+/// of the configured constellation, and aggregate transcript commits signed by at least
+/// `BounceConfig::required_signers` signers, none of which signed another message in the slot.
+///
+/// Commits of the transcript whose signature doesn't verify, or which carry another public key
+/// than the one the signer's included commit was signed with, are ignored, so that a forged
+/// commit can't make an honest signer look like an equivocator.
+pub fn validate_slot_transcript(
+    commits: &[Commit],
+    aggregate: &Commit,
+    config: &BounceConfig,
+) -> Result<(), TranscriptError> {
+    verify_aggregate(aggregate, 0).map_err(TranscriptError::InvalidAggregate)?;
+    if !in_domain(&config.domain, &aggregate.msg) {
+        return Err(TranscriptError::WrongDomain);
+    }
+
+    let required = config.required_signers(config.num_cubesats as usize);
+    if aggregate.signers.len() < required {
+        return Err(TranscriptError::InsufficientSigners {
+            signers: aggregate.signers.len(),
+            required,
+        });
+    }
+
+    // Valid commits of the slot by signer.
+    let mut signed: HashMap<u32, Vec<&Commit>> = HashMap::new();
+    for commit in commits {
+        if !commit.aggregated && commit.i == aggregate.i && verify_signature(commit) {
+            signed.entry(commit.signer_id).or_default().push(commit);
+        }
+    }
+
+    let mut public_keys = Vec::with_capacity(aggregate.signers.len());
+    for &signer_id in aggregate.signers.iter() {
+        let commits = signed.get(&signer_id).map_or(&[][..], |c| c.as_slice());
+        let included = commits
+            .iter()
+            .find(|c| c.typ == aggregate.typ && c.msg == aggregate.msg)
+            .ok_or(TranscriptError::MissingCommit { signer_id })?;
+        if commits.iter().any(|c| {
+            c.public_key == included.public_key
+                && (c.typ != aggregate.typ || c.msg != aggregate.msg)
+        }) {
+            return Err(TranscriptError::Equivocation { signer_id });
+        }
+        public_keys.push(included.public_key.as_slice());
+    }
+
+    verify_aggregate_against(aggregate, &public_keys).map_err(TranscriptError::InvalidAggregate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::CommitType;
+    use crate::{
+        expected_aggregate, noncommit_message, precommit_message, CommitBuilder, LocalSigner,
+    };
+
+    // Precommits of slot 1 by signers 0 to n-1, and the aggregate of the first k of them.
+    fn transcript(n: u32, k: usize) -> (Vec<LocalSigner>, Vec<Commit>, Commit) {
+        let msg = precommit_message(&[], 0, 1);
+        let signers: Vec<LocalSigner> = (0..n).map(|_| LocalSigner::random()).collect();
+        let commits: Vec<Commit> = signers
+            .iter()
+            .enumerate()
+            .map(|(id, signer)| {
+                CommitBuilder::precommit(1, 0, msg.clone())
+                    .signer_id(id as u32)
+                    .sign_with(signer)
+                    .unwrap()
+                    .build()
+            })
+            .collect();
+
+        let (signature, public_key) = expected_aggregate(&commits[..k]).unwrap();
+        let aggregate = Commit {
+            typ: CommitType::Precommit.into(),
+            i: 1,
+            j: 1,
+            msg,
+            public_key,
+            signature,
+            aggregated: true,
+            signer_count: k as u32,
+            signers: (0..k as u32).collect(),
+            ..Default::default()
+        };
+        (signers, commits, aggregate)
+    }
+
+    fn config(num_cubesats: u32) -> BounceConfig {
+        BounceConfig {
+            num_cubesats,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_transcript() {
+        let (_, commits, aggregate) = transcript(4, 3);
+        assert_eq!(
+            validate_slot_transcript(&commits, &aggregate, &config(4)),
+            Ok(())
+        );
+
+        // Every signer of the aggregate must show up in the transcript.
+        assert_eq!(
+            validate_slot_transcript(&commits[1..], &aggregate, &config(4)),
+            Err(TranscriptError::MissingCommit { signer_id: 0 })
+        );
+    }
+
+    #[test]
+    fn insufficient_signers() {
+        let (_, commits, aggregate) = transcript(4, 2);
+        assert_eq!(
+            validate_slot_transcript(&commits, &aggregate, &config(4)),
+            Err(TranscriptError::InsufficientSigners {
+                signers: 2,
+                required: 3
+            })
+        );
+    }
+
+    #[test]
+    fn equivocator_invalidates_inclusion() {
+        let (signers, mut commits, aggregate) = transcript(4, 3);
+
+        // Signer 1 also signed a noncommit for the slot.
+        commits.push(
+            CommitBuilder::noncommit(1, 0, noncommit_message(&[], 0, 1))
+                .signer_id(1)
+                .sign_with(&signers[1])
+                .unwrap()
+                .build(),
+        );
+        assert_eq!(
+            validate_slot_transcript(&commits, &aggregate, &config(4)),
+            Err(TranscriptError::Equivocation { signer_id: 1 })
+        );
+
+        // A forged commit doesn't frame signer 2.
+        let mut forged = commits.pop().unwrap();
+        forged.signer_id = 2;
+        commits.push(forged);
+        assert_eq!(
+            validate_slot_transcript(&commits, &aggregate, &config(4)),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use crate::{with_compressed_points, Commit};
+use bls_signatures_rs::bn256::Bn256;
+use bls_signatures_rs::MultiSignature;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// Errors returned when an aggregated commit fails verification.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerifyError {
+    /// The commit isn't an aggregate.
+    NotAggregated,
+    /// The aggregate claims fewer signers than required.
+    InsufficientSigners { claimed: usize, required: usize },
+    /// The claimed signer count doesn't match the distinct signer ids carried by the aggregate.
+    SignerCountMismatch { claimed: usize, signers: usize },
+    /// The aggregate public key doesn't match the keys of the expected signers.
+    PublicKeyMismatch,
+    /// The aggregate signature doesn't verify against the aggregate public key.
+    InvalidSignature,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::NotAggregated => write!(f, "commit is not an aggregate"),
+            VerifyError::InsufficientSigners { claimed, required } => write!(
+                f,
+                "aggregate claims {} signers but at least {} are required",
+                claimed, required
+            ),
+            VerifyError::SignerCountMismatch { claimed, signers } => write!(
+                f,
+                "aggregate claims {} signers but carries {} distinct signer ids",
+                claimed, signers
+            ),
+            VerifyError::PublicKeyMismatch => {
+                write!(f, "aggregate public key does not match the signer keys")
+            }
+            VerifyError::InvalidSignature => write!(f, "aggregate signature is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Verifies that an aggregated commit claims at least `k` distinct signers and that its signature
+/// is valid for the message and aggregate public key it carries.
+///
+/// This gives no k-of-n guarantee on its own: nothing ties the carried public key to the claimed
+/// signers, so a single signer can pass its own commit off as an aggregate of any k ids. Use
+/// `verify_aggregate_against` with the signers' keys, e.g. from a `PeerRegistry`, for that.
+pub fn verify_aggregate(commit: &Commit, k: usize) -> Result<(), VerifyError> {
+    if !commit.aggregated {
+        return Err(VerifyError::NotAggregated);
+    }
+
+    let claimed = commit.signer_count as usize;
+    let signers: HashSet<u32> = commit.signers.iter().copied().collect();
+    if signers.len() != claimed || commit.signers.len() != claimed {
+        return Err(VerifyError::SignerCountMismatch {
+            claimed,
+            signers: signers.len(),
+        });
+    }
+
+    if claimed < k {
+        return Err(VerifyError::InsufficientSigners {
+            claimed,
+            required: k,
+        });
+    }
+
+    // The aggregate may carry uncompressed points, see `BounceConfig::uncompressed_points`.
+    let commit = with_compressed_points(commit).map_err(|_| VerifyError::InvalidSignature)?;
+    Bn256
+        .verify(&commit.signature, &commit.msg, &commit.public_key)
+        .map_err(|_| VerifyError::InvalidSignature)
+}
+
+/// Verifies an aggregated commit against the public keys of its signers, instead of trusting the
+/// aggregate public key it carries.
+///
+/// The keys are aggregated and must match `commit.public_key` before the signature is checked.
+pub fn verify_aggregate_against(commit: &Commit, signer_keys: &[&[u8]]) -> Result<(), VerifyError> {
+    if !commit.aggregated {
+        return Err(VerifyError::NotAggregated);
+    }
+
+    let commit = with_compressed_points(commit).map_err(|_| VerifyError::PublicKeyMismatch)?;
+    let public_key = Bn256
+        .aggregate_public_keys(signer_keys)
+        .map_err(|_| VerifyError::PublicKeyMismatch)?;
+    if public_key != commit.public_key {
+        return Err(VerifyError::PublicKeyMismatch);
+    }
+
+    Bn256
+        .verify(&commit.signature, &commit.msg, &commit.public_key)
+        .map_err(|_| VerifyError::InvalidSignature)
+}
+
+/// Whether the signature of a commit verifies against its own message and public key.
+pub fn verify_signature(commit: &Commit) -> bool {
+    match with_compressed_points(commit) {
+        Ok(commit) => Bn256
+            .verify(&commit.signature, &commit.msg, &commit.public_key)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Splits commits into those whose signature verifies against their own message and public key,
+/// and those whose signature doesn't.
+pub fn partition_valid(commits: &[Commit]) -> (Vec<Commit>, Vec<Commit>) {
+    commits.iter().cloned().partition(verify_signature)
+}
+
+/// Index of a commit whose inclusion keeps the aggregate of `commits` from verifying against
+/// `msg`, or None if the aggregate verifies. Halves of the set are aggregated and checked in turn,
+/// so a single bad commit is found with a logarithmic number of pairings. With several bad
+/// commits, one of them is returned.
+pub fn find_bad_commit(commits: &[Commit], msg: &[u8]) -> Option<usize> {
+    if commits.is_empty() || aggregate_verifies(commits, msg) {
+        return None;
+    }
+
+    // The aggregate of commits[start..end] doesn't verify.
+    let (mut start, mut end) = (0, commits.len());
+    while end - start > 1 {
+        let mid = start + (end - start) / 2;
+        if aggregate_verifies(&commits[start..mid], msg) {
+            start = mid;
+        } else {
+            end = mid;
+        }
+    }
+    Some(start)
+}
+
+// Whether the aggregate signature of the commits verifies against `msg` and their aggregate
+// public key.
+fn aggregate_verifies(commits: &[Commit], msg: &[u8]) -> bool {
+    let commits: Vec<_> = match commits.iter().map(with_compressed_points).collect() {
+        Ok(commits) => commits,
+        Err(_) => return false,
+    };
+    let signatures: Vec<&[u8]> = commits.iter().map(|c| c.signature.as_slice()).collect();
+    let public_keys: Vec<&[u8]> = commits.iter().map(|c| c.public_key.as_slice()).collect();
+
+    match (
+        Bn256.aggregate_signatures(&signatures),
+        Bn256.aggregate_public_keys(&public_keys),
+    ) {
+        (Ok(signature), Ok(public_key)) => Bn256.verify(&signature, msg, &public_key).is_ok(),
+        _ => false,
+    }
+}
+
+/// Check of the signature of a received commit, returning whether it's valid. It's run on
+/// blocking threads, so it's shared with the tasks verifying commits.
+pub type SignatureVerifier = Arc<dyn Fn(&Commit) -> bool + Send + Sync>;
+
+/// Checks the signature of each commit against its own message and public key, spreading the
+/// commits over `threads` blocking tasks. Returns whether each commit is valid, in order.
+pub async fn verify_signatures(commits: &[Commit], threads: usize) -> Vec<bool> {
+    verify_signatures_with(commits, threads, Arc::new(verify_signature)).await
+}
+
+/// Same as `verify_signatures`, checking each commit with `verifier`.
+pub async fn verify_signatures_with(
+    commits: &[Commit],
+    threads: usize,
+    verifier: SignatureVerifier,
+) -> Vec<bool> {
+    if commits.is_empty() {
+        return Vec::new();
+    }
+
+    let chunk_size = (commits.len() + threads.max(1) - 1) / threads.max(1);
+    let tasks: Vec<_> = commits
+        .chunks(chunk_size)
+        .map(|chunk| {
+            let chunk = chunk.to_vec();
+            let verifier = verifier.clone();
+            tokio::task::spawn_blocking(move || {
+                chunk.iter().map(|c| verifier(c)).collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut valid = Vec::with_capacity(commits.len());
+    for task in tasks {
+        valid.extend(task.await.expect("signature verification panicked"));
+    }
+    valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    fn signed_commit(msg: &[u8], signed_msg: &[u8], signer_id: u32) -> Commit {
+        let mut rng = thread_rng();
+        let private_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+
+        Commit {
+            msg: msg.to_vec(),
+            public_key: Bn256.derive_public_key(&private_key).unwrap(),
+            signature: Bn256.sign(&private_key, signed_msg).unwrap(),
+            signer_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn find_bad_commit_bisects() {
+        let msg = b"hello";
+        let mut commits: Vec<Commit> = (0..7).map(|id| signed_commit(msg, msg, id)).collect();
+        assert_eq!(find_bad_commit(&commits, msg), None);
+
+        commits[4] = signed_commit(msg, b"other", 4);
+        assert_eq!(find_bad_commit(&commits, msg), Some(4));
+
+        // A malformed signature is found too.
+        commits[4] = signed_commit(msg, msg, 4);
+        commits[0].signature = vec![0; 3];
+        assert_eq!(find_bad_commit(&commits, msg), Some(0));
+    }
+
+    #[tokio::test]
+    async fn verify_signatures_batch() {
+        let msg = b"hello";
+        let commits: Vec<Commit> = (0..16)
+            .map(|id| {
+                // Every fifth commit signs another message.
+                let signed_msg: &[u8] = if id % 5 == 0 { b"other" } else { msg };
+                signed_commit(msg, signed_msg, id)
+            })
+            .collect();
+        let expected: Vec<bool> = (0..16).map(|id| id % 5 != 0).collect();
+
+        let serial: Vec<bool> = commits
+            .iter()
+            .map(|c| Bn256.verify(&c.signature, &c.msg, &c.public_key).is_ok())
+            .collect();
+        let parallel = verify_signatures(&commits, 4).await;
+
+        assert_eq!(serial, expected);
+        assert_eq!(parallel, expected);
+    }
+
+    #[test]
+    fn verify_aggregate_not_aggregated() {
+        let commit = Commit {
+            aggregated: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            verify_aggregate(&commit, 1),
+            Err(VerifyError::NotAggregated)
+        );
+    }
+
+    #[test]
+    fn verify_aggregate_signer_count_mismatch() {
+        let commit = Commit {
+            aggregated: true,
+            signer_count: 3,
+            signers: vec![1, 1, 2],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            verify_aggregate(&commit, 1),
+            Err(VerifyError::SignerCountMismatch {
+                claimed: 3,
+                signers: 2
+            })
+        );
+    }
+
+    #[test]
+    fn verify_aggregate_against_signer_keys() {
+        let msg = "hello".as_bytes();
+        let commits: Vec<Commit> = (1..=3).map(|id| signed_commit(msg, msg, id)).collect();
+        let signatures: Vec<&[u8]> = commits.iter().map(|c| c.signature.as_slice()).collect();
+        let public_keys: Vec<&[u8]> = commits.iter().map(|c| c.public_key.as_slice()).collect();
+
+        let aggregate = Commit {
+            aggregated: true,
+            msg: msg.to_vec(),
+            public_key: Bn256.aggregate_public_keys(&public_keys).unwrap(),
+            signature: Bn256.aggregate_signatures(&signatures).unwrap(),
+            signer_count: 3,
+            signers: vec![1, 2, 3],
+            ..Default::default()
+        };
+
+        assert_eq!(verify_aggregate_against(&aggregate, &public_keys), Ok(()));
+
+        // Leaving out one of the signers yields a different aggregate public key.
+        assert_eq!(
+            verify_aggregate_against(&aggregate, &public_keys[..2]),
+            Err(VerifyError::PublicKeyMismatch)
+        );
+
+        let other = signed_commit(msg, msg, 4);
+        let wrong_keys = vec![public_keys[0], public_keys[1], other.public_key.as_slice()];
+        assert_eq!(
+            verify_aggregate_against(&aggregate, &wrong_keys),
+            Err(VerifyError::PublicKeyMismatch)
+        );
+    }
+
+    #[test]
+    fn partition_valid_test() {
+        let msg = "hello".as_bytes();
+        let commits = vec![
+            signed_commit(msg, msg, 0),
+            signed_commit(msg, "bye".as_bytes(), 1),
+            signed_commit(msg, msg, 2),
+            Commit {
+                msg: msg.to_vec(),
+                signer_id: 3,
+                ..Default::default()
+            },
+        ];
+
+        let (valid, invalid) = partition_valid(&commits);
+        let ids = |commits: &[Commit]| commits.iter().map(|c| c.signer_id).collect::<Vec<_>>();
+        assert_eq!(ids(&valid), vec![0, 2]);
+        assert_eq!(ids(&invalid), vec![1, 3]);
+    }
+}
@@ -0,0 +1,136 @@
+//! End to end test of the space station over gRPC: a real tonic client streams precommits of
+//! simulated peers to the `space-station` binary and checks the aggregates it gets back.
+
+use bounce::bounce_satellite_client::BounceSatelliteClient;
+use bounce::{supermajority, verify_aggregate, CommitBuilder, LocalSigner};
+use rand::{thread_rng, Rng};
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tokio::time::{interval, sleep, timeout};
+use tonic::transport::Channel;
+use tonic::Code;
+
+const NUM_CUBESATS: u32 = 4;
+// Requests are sent once per slot, early enough for the Bounce units to sign in phase 1 or 2.
+const SLOT_DURATION: Duration = Duration::from_secs(4);
+
+// Space station process, killed on drop along with its config file.
+struct SpaceStation {
+    child: Child,
+    config_path: PathBuf,
+}
+
+impl SpaceStation {
+    fn start(port: u16) -> SpaceStation {
+        let config_path = std::env::temp_dir().join(format!(
+            "bounce-grpc-{}-{}.toml",
+            std::process::id(),
+            thread_rng().gen::<u64>()
+        ));
+        fs::write(
+            &config_path,
+            format!(
+                "num_cubesats = {}\nslot_duration = {}\nphase1_duration = 2\nphase2_duration = 1\n",
+                NUM_CUBESATS,
+                SLOT_DURATION.as_secs()
+            ),
+        )
+        .unwrap();
+
+        let child = Command::new(env!("CARGO_BIN_EXE_space-station"))
+            .args([
+                "-a",
+                "127.0.0.1",
+                "-p",
+                &port.to_string(),
+                "--log-to-stdout",
+            ])
+            .arg("-c")
+            .arg(&config_path)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        SpaceStation { child, config_path }
+    }
+
+    fn is_running(&mut self) -> bool {
+        self.child.try_wait().unwrap().is_none()
+    }
+}
+
+impl Drop for SpaceStation {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.config_path);
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn connect(port: u16) -> BounceSatelliteClient<Channel> {
+    let dst = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if let Ok(client) = BounceSatelliteClient::connect(dst.clone()).await {
+            return client;
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    panic!("space station didn't start listening on {}", dst);
+}
+
+#[tokio::test]
+async fn grpc_aggregates_verify() {
+    let port = free_port();
+    let mut space_station = SpaceStation::start(port);
+    let mut client = connect(port).await;
+
+    let mut slot_ticker = interval(SLOT_DURATION);
+    for peer in 0..3u32 {
+        slot_ticker.tick().await;
+
+        let msg = format!("message from peer {}", peer).into_bytes();
+        let precommit = CommitBuilder::precommit(1, 0, msg.clone())
+            .signer_id(100 + peer)
+            .sign_with(&LocalSigner::random())
+            .unwrap()
+            .build();
+
+        let aggregate = timeout(SLOT_DURATION, client.bounce(precommit))
+            .await
+            .expect("no aggregate within the slot")
+            .unwrap()
+            .into_inner();
+        assert_eq!(aggregate.msg, msg);
+        verify_aggregate(&aggregate, supermajority(NUM_CUBESATS as usize)).unwrap();
+
+        // A commit whose signature doesn't verify is rejected instead of left to the Bounce
+        // units, and the server keeps serving the next slots.
+        if peer == 0 {
+            let mut malformed = CommitBuilder::precommit(1, 0, b"malformed".to_vec())
+                .signer_id(200)
+                .sign_with(&LocalSigner::random())
+                .unwrap()
+                .build();
+            malformed.signature = vec![1, 2, 3];
+
+            let status = timeout(SLOT_DURATION, client.bounce(malformed))
+                .await
+                .expect("malformed commit neither rejected nor answered")
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert!(space_station.is_running());
+        }
+    }
+    assert!(space_station.is_running());
+}